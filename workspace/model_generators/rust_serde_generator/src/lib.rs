@@ -9,9 +9,9 @@ use proc_macro2::TokenStream;
 use proc_macro2::{Ident, Span};
 use quote::quote;
 use schema_discovery::{SchemaDiscoverable, SchemaDiscoverer};
-use schema_registry::SchemaRegistry;
+use schema_registry::{DynamicScope, SchemaRegistry};
 use serde_json_schema::{AnyType, StringOrStringArray};
-use serde_json_schema::{BooleanOrSchema, Schema};
+use serde_json_schema::{BooleanOrSchema, Schema, SchemaOrSchemaArray};
 
 // TODO validation>?
 // TODO schema wrapper with name or derived name
@@ -29,12 +29,15 @@ fn capatalize(s: &str) -> String {
 }
 
 fn get_first_resource_from_url(url: &str) -> Option<String> {
-    url.split('/').last().map(|s| s.to_owned()).map(|url_end| {
-        url_end
-            .chars()
-            .take_while(|c| *c != '.')
-            .collect::<String>()
-    })
+    url.split('/')
+        .next_back()
+        .map(|s| s.to_owned())
+        .map(|url_end| {
+            url_end
+                .chars()
+                .take_while(|c| *c != '.')
+                .collect::<String>()
+        })
 }
 
 fn snake_case(s: &str) -> String {
@@ -94,7 +97,6 @@ impl<'a> Iterator for ModuleGenerator<'a> {
                         // FIXME unwrap here
                         schema.get_id().is_some() && schema.get_id().unwrap() == root_schema_id
                     })
-                    .take()
                     .map(|schema| (schema.to_owned(), schema.get_id().unwrap()));
 
                 let same_root_iter = self
@@ -117,7 +119,7 @@ impl<'a> Iterator for ModuleGenerator<'a> {
                     .chain(root_schema)
                     .map(|(schema, root_schema_id)| {
                         if schema.one_of.is_some() {
-                            to_enum(schema, self.registry)
+                            to_enum(schema, &root_schema_id, self.registry)
                         } else {
                             to_struct(schema, root_schema_id, self.type_mapping, self.registry)
                         }
@@ -169,17 +171,26 @@ fn to_struct(
     })
 }
 
-fn to_enum(parent_schema: Schema, registry: &SchemaRegistry) -> Result<TokenStream> {
+fn to_enum(
+    parent_schema: Schema,
+    root_schema_id: &str,
+    registry: &SchemaRegistry,
+) -> Result<TokenStream> {
     let schemas = parent_schema
         .one_of
         .as_ref()
         .unwrap()
         .iter()
-        .map(|schema| match &schema.reference {
-            Some(reference) => registry
-                .get(reference)
-                .ok_or(GeneratorError::NoSchemasFound),
-            None => Ok(schema),
+        .map(|schema| {
+            let schema = schema.as_schema().ok_or(GeneratorError::NoNameForSchema)?;
+
+            match &schema.reference {
+                Some(reference) => registry
+                    .resolve(root_schema_id, reference)
+                    .map(|resolved| resolved.schema())
+                    .ok_or(GeneratorError::NoSchemasFound),
+                None => Ok(schema),
+            }
         })
         .collect::<Result<Vec<&Schema>, GeneratorError>>()?;
 
@@ -193,9 +204,8 @@ fn to_enum(parent_schema: Schema, registry: &SchemaRegistry) -> Result<TokenStre
                     .properties
                     .iter()
                     .flat_map(|map| map.get(*parent_property))
-                    .filter(|value| matches!(&value.schema_const, Some(AnyType::String(_))))
-                    .last()
-                    .is_some()
+                    .filter_map(BooleanOrSchema::as_schema)
+                    .any(|value| matches!(&value.schema_const, Some(AnyType::String(_))))
             })
         })
         .last()
@@ -220,6 +230,7 @@ fn to_enum(parent_schema: Schema, registry: &SchemaRegistry) -> Result<TokenStre
                 .as_ref()
                 .expect("Already should've errored")
                 .get(tag)
+                .and_then(BooleanOrSchema::as_schema)
                 .expect("Already should've errored")
                 .schema_const
                 .as_ref()
@@ -255,7 +266,7 @@ fn to_enum(parent_schema: Schema, registry: &SchemaRegistry) -> Result<TokenStre
 
 struct FieldGenerator<'a> {
     struct_span: Span,
-    properties: Vec<(String, Schema)>,
+    properties: Vec<(String, BooleanOrSchema)>,
     schema: Schema,
     root_schema_id: String,
     type_mapping: &'a TypeMapping,
@@ -285,7 +296,9 @@ impl<'a> FieldGenerator<'a> {
             .take()
             .into_iter()
             .flatten()
-            .collect::<Vec<(String, Schema)>>();
+            // A property with the false schema can never be present, so it gets no field.
+            .filter(|(_, schema)| schema.as_bool() != Some(false))
+            .collect::<Vec<(String, BooleanOrSchema)>>();
 
         properties.sort_by(|a, b| String::cmp(&b.0, &a.0));
 
@@ -299,22 +312,43 @@ impl<'a> FieldGenerator<'a> {
         }
     }
 
-    fn next_field(&mut self, property_name: String, schema: Schema) -> Result<TokenStream> {
-        let (schema, root_schema_id) = match schema.reference {
-            Some(reference) => {
-                let reference_root = reference
-                    .split_once('#')
-                    .map(|r| r.0.to_owned())
-                    .unwrap_or(reference.clone());
-
-                let schema = self
-                    .registry
-                    .get(&reference)
-                    .ok_or(GeneratorError::UnresolvableReference(reference))?
-                    .to_owned();
-
-                (schema, reference_root)
+    fn next_field(
+        &mut self,
+        property_name: String,
+        schema: BooleanOrSchema,
+    ) -> Result<TokenStream> {
+        let schema = match schema {
+            BooleanOrSchema::InnerSchema(schema) => *schema,
+            BooleanOrSchema::Boolean(_) => Err(GeneratorError::PropertyMissingTypeForField(
+                property_name.to_owned(),
+            ))?,
+        };
+
+        let resolved = match (&schema.reference, &schema.dynamic_reference) {
+            (Some(reference), _) => Some(
+                self.registry
+                    .resolve(&self.root_schema_id, reference)
+                    .ok_or(GeneratorError::UnresolvableReference(reference.to_owned()))?,
+            ),
+            (None, Some(reference)) => {
+                // Generated code has no evaluation to follow, so the only resource in scope is
+                // the one the struct is generated from.
+                let mut scope = DynamicScope::new();
+                if let Some(resource) = self.registry.get(&self.root_schema_id) {
+                    scope.enter(resource, self.root_schema_id.clone());
+                }
+
+                Some(
+                    self.registry
+                        .resolve_dynamic(&self.root_schema_id, reference, &scope)
+                        .ok_or(GeneratorError::UnresolvableReference(reference.to_owned()))?,
+                )
             }
+            (None, None) => None,
+        };
+
+        let (schema, root_schema_id) = match resolved {
+            Some(resolved) => (resolved.schema().to_owned(), resolved.base_uri().to_owned()),
             None => (schema, self.root_schema_id.clone()),
         };
 
@@ -354,10 +388,10 @@ impl<'a> FieldGenerator<'a> {
                     ))?;
 
                 let schema = match items {
-                    BooleanOrSchema::Boolean(_) => Err(GeneratorError::ArrayDoesNotHaveSchema(
+                    SchemaOrSchemaArray::Schema(BooleanOrSchema::InnerSchema(schema)) => schema,
+                    _ => Err(GeneratorError::ArrayDoesNotHaveSchema(
                         property_name.to_owned(),
                     ))?,
-                    BooleanOrSchema::InnerSchema(schema) => schema,
                 };
 
                 let json_type = match schema.schema_type.clone().ok_or(
@@ -1029,3 +1063,143 @@ fn device_type_modified_example() {
 
     assert_eq!(result, expected_result);
 }
+
+#[test]
+fn local_reference_example() {
+    let json_string = r##"{
+        "$id": "https://example.com/order.schema.json",
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": {
+            "product": {
+                "$ref": "#ProductSchema"
+            },
+            "replacement": {
+                "$ref": "#/$defs/product"
+            }
+        },
+        "required": ["product"],
+        "$defs": {
+            "product": {
+                "$anchor": "ProductSchema",
+                "title": "Product",
+                "type": "object",
+                "properties": {
+                    "name": {
+                        "type": "string"
+                    }
+                }
+            }
+        }
+    }"##;
+    let schema: Schema = serde_json::from_str(json_string).unwrap();
+
+    let registry = SchemaRegistry::new()
+        .add_internally_identified_schema(schema.clone())
+        .unwrap()
+        .discover()
+        .unwrap();
+
+    let result = Generator::new()
+        .schema_registry(registry)
+        .generate(schema)
+        .unwrap();
+
+    let file_contents = quote! {
+        use serde::{Serialize, Deserialize};
+
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        pub struct Product {
+            #[serde(rename = "name")]
+            pub name: Option<String>,
+        }
+        ///https://example.com/order.schema.json
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        pub struct Order {
+            #[serde(rename = "product")]
+            pub product: crate::order::Product,
+            #[serde(rename = "replacement")]
+            pub replacement: Option<crate::order::Product>,
+        }
+    };
+
+    let syntax_tree = syn::parse2(file_contents).unwrap();
+    let expected_result = prettyplease::unparse(&syntax_tree);
+
+    assert_eq!(result, expected_result);
+}
+
+/// https://json-schema.org/draft/2020-12/json-schema-core#name-dynamic-references-with-dyn
+#[test]
+fn dynamic_reference_example() {
+    let list_json_string = r##"{
+        "$id": "https://example.com/list.schema.json",
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "$defs": {
+            "item": {
+                "$dynamicAnchor": "item",
+                "title": "Item",
+                "type": "object"
+            }
+        }
+    }"##;
+    let order_history_json_string = r##"{
+        "$id": "https://example.com/order-history.schema.json",
+        "$schema": "https://json-schema.org/draft/2020-12/schema",
+        "type": "object",
+        "properties": {
+            "latest": {
+                "$dynamicRef": "https://example.com/list.schema.json#item"
+            }
+        },
+        "$defs": {
+            "order": {
+                "$dynamicAnchor": "item",
+                "title": "Order",
+                "type": "object",
+                "properties": {
+                    "id": {
+                        "type": "string"
+                    }
+                }
+            }
+        }
+    }"##;
+    let list_schema: Schema = serde_json::from_str(list_json_string).unwrap();
+    let order_history_schema: Schema = serde_json::from_str(order_history_json_string).unwrap();
+
+    let registry = SchemaRegistry::new()
+        .add_internally_identified_schema(list_schema)
+        .unwrap()
+        .add_internally_identified_schema(order_history_schema.clone())
+        .unwrap()
+        .discover()
+        .unwrap();
+
+    let result = Generator::new()
+        .schema_registry(registry)
+        .generate(order_history_schema)
+        .unwrap();
+
+    let file_contents = quote! {
+        use serde::{Serialize, Deserialize};
+
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        pub struct Order {
+            #[serde(rename = "id")]
+            pub id: Option<String>,
+        }
+        ///https://example.com/order-history.schema.json
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+        pub struct OrderHistory {
+            #[serde(rename = "latest")]
+            pub latest: Option<crate::order_history::Order>,
+        }
+    };
+
+    let syntax_tree = syn::parse2(file_contents).unwrap();
+    let expected_result = prettyplease::unparse(&syntax_tree);
+
+    assert_eq!(result, expected_result);
+}
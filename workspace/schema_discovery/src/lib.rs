@@ -1,9 +1,149 @@
-use std::iter;
+use serde_json_schema::{walk_schema, BooleanOrSchema, JsonPointer, Position, Schema, Visitor};
 
-use serde_json_schema::{
-    Schema, DEFINITIONS_PATH, DEPENDENT_SCHEMAS_PATH, ITEMS_PATH, PATTERN_PROPERTIES_PATH,
-    PROPERTIES_PATH,
-};
+/// Appends a JSON pointer segment to a schema path, starting the fragment when the path doesn't
+/// have one yet, e.g. `https://example.com/person.schema.json#/properties/age`.
+pub fn append_to_path(path: &str, segment: &str) -> String {
+    match path.contains('#') {
+        true => format!("{path}/{segment}"),
+        false => format!("{path}#/{segment}"),
+    }
+}
+
+/// Resolves a URI reference against a base URI as described in RFC 3986 section 5.2, e.g.
+/// `address.json` against `https://example.com/person.json` is `https://example.com/address.json`.
+pub fn resolve_uri(base: &str, reference: &str) -> String {
+    let reference = UriComponents::parse(reference);
+    let base = UriComponents::parse(base);
+
+    let (authority, path, query) = match (reference.scheme, reference.authority) {
+        (Some(_), _) | (None, Some(_)) => (
+            reference.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        ),
+        (None, None) if reference.path.is_empty() => (
+            base.authority,
+            base.path.to_owned(),
+            reference.query.or(base.query),
+        ),
+        (None, None) if reference.path.starts_with('/') => (
+            base.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        ),
+        (None, None) => {
+            let merged = match (base.authority, base.path.rfind('/')) {
+                (Some(_), None) if base.path.is_empty() => format!("/{}", reference.path),
+                (_, Some(index)) => format!("{}{}", &base.path[..=index], reference.path),
+                (_, None) => reference.path.to_owned(),
+            };
+
+            (
+                base.authority,
+                remove_dot_segments(&merged),
+                reference.query,
+            )
+        }
+    };
+
+    let mut resolved = String::new();
+
+    if let Some(scheme) = reference.scheme.or(base.scheme) {
+        resolved.push_str(scheme);
+        resolved.push(':');
+    }
+
+    if let Some(authority) = authority {
+        resolved.push_str("//");
+        resolved.push_str(authority);
+    }
+
+    resolved.push_str(&path);
+
+    if let Some(query) = query {
+        resolved.push('?');
+        resolved.push_str(query);
+    }
+
+    if let Some(fragment) = reference.fragment {
+        resolved.push('#');
+        resolved.push_str(fragment);
+    }
+
+    resolved
+}
+
+/// The components of a URI reference, split as in RFC 3986 appendix B.
+struct UriComponents<'a> {
+    scheme: Option<&'a str>,
+    authority: Option<&'a str>,
+    path: &'a str,
+    query: Option<&'a str>,
+    fragment: Option<&'a str>,
+}
+
+impl<'a> UriComponents<'a> {
+    fn parse(uri: &'a str) -> Self {
+        let (rest, fragment) = match uri.split_once('#') {
+            Some((rest, fragment)) => (rest, Some(fragment)),
+            None => (uri, None),
+        };
+
+        let (rest, query) = match rest.split_once('?') {
+            Some((rest, query)) => (rest, Some(query)),
+            None => (rest, None),
+        };
+
+        let (scheme, rest) = match rest.find(':') {
+            Some(index) if index > 0 && !rest[..index].contains('/') => {
+                (Some(&rest[..index]), &rest[index + 1..])
+            }
+            _ => (None, rest),
+        };
+
+        let (authority, path) = match rest.strip_prefix("//") {
+            Some(rest) => match rest.find('/') {
+                Some(index) => (Some(&rest[..index]), &rest[index..]),
+                None => (Some(rest), ""),
+            },
+            None => (None, rest),
+        };
+
+        UriComponents {
+            scheme,
+            authority,
+            path,
+            query,
+            fragment,
+        }
+    }
+}
+
+fn remove_dot_segments(path: &str) -> String {
+    let segments: Vec<&str> = path.split('/').collect();
+    // The empty segment before the leading slash of an absolute path is never removed.
+    let floor = usize::from(path.starts_with('/'));
+    let mut output: Vec<&str> = Vec::new();
+
+    for (index, segment) in segments.iter().enumerate() {
+        let is_last = index == segments.len() - 1;
+
+        match *segment {
+            "." | ".." => {
+                if *segment == ".." && output.len() > floor {
+                    output.pop();
+                }
+
+                if is_last {
+                    output.push("");
+                }
+            }
+            segment => output.push(segment),
+        }
+    }
+
+    output.join("/")
+}
 
 pub trait SchemaDiscoverable {
     fn discover(&self) -> SchemaDiscoverer<'_>;
@@ -24,6 +164,7 @@ impl SchemaDiscoverable for &Schema {
 pub struct DiscoveredSchema<'a> {
     id: String,
     root_schema_id: String,
+    canonical_uri: Option<String>,
     schema: &'a Schema,
 }
 
@@ -32,6 +173,12 @@ impl<'a> DiscoveredSchema<'a> {
         &self.id
     }
 
+    /// The absolute URI of the schema's own `$id`, resolved against the base URI of the schema
+    /// it is nested in.
+    pub fn canonical_uri(&self) -> Option<&str> {
+        self.canonical_uri.as_deref()
+    }
+
     pub fn root_schema_id(&self) -> &str {
         &self.root_schema_id
     }
@@ -53,32 +200,38 @@ impl<'a> DiscoveredSchema<'a> {
 }
 
 pub struct SchemaDiscoverer<'a> {
-    iter: std::vec::IntoIter<PathableSchema<'a>>,
-    discovering: Option<std::vec::IntoIter<PathableSchema<'a>>>,
-    nested: Vec<std::vec::IntoIter<PathableSchema<'a>>>,
+    iter: std::vec::IntoIter<(Vec<String>, DiscoveredSchema<'a>)>,
 }
 
 impl<'a> SchemaDiscoverer<'a> {
     fn new(schema: &'a Schema) -> Self {
-        match schema.dollar_id.to_owned().or(schema.id.to_owned()) {
-            Some(id) => {
-                let pathable_schema = PathableSchema {
-                    root_path: id.clone(),
-                    path: id,
-                    schema,
-                };
-
-                Self {
-                    iter: pathable_schema.into_iter(),
-                    discovering: None,
-                    nested: Vec::new(),
-                }
-            }
-            None => Self {
+        let Some(id) = schema.get_id() else {
+            return Self {
                 iter: Vec::new().into_iter(),
-                discovering: None,
-                nested: Vec::new(),
+            };
+        };
+
+        let id = canonical(resolve_uri("", &id));
+        let mut collector = DiscoveryCollector {
+            current: PathableSchema {
+                root_path: id.clone(),
+                path: id,
+                is_resource: true,
+                schema,
             },
+            ancestry: Vec::new(),
+            discovered: Vec::new(),
+        };
+        walk_schema(&mut collector, &JsonPointer::root(), schema);
+
+        // Depth first with siblings ordered by path, which keeps the schemas sharing a root
+        // together.
+        collector
+            .discovered
+            .sort_by(|(left, _), (right, _)| left.cmp(right));
+
+        Self {
+            iter: collector.discovered.into_iter(),
         }
     }
 }
@@ -86,6 +239,7 @@ impl<'a> SchemaDiscoverer<'a> {
 impl<'a> From<PathableSchema<'a>> for DiscoveredSchema<'a> {
     fn from(value: PathableSchema<'a>) -> Self {
         DiscoveredSchema {
+            canonical_uri: value.is_resource.then(|| value.root_path.clone()),
             root_schema_id: value.root_path,
             id: value.path,
             schema: value.schema,
@@ -97,24 +251,7 @@ impl<'a> Iterator for SchemaDiscoverer<'a> {
     type Item = DiscoveredSchema<'a>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        loop {
-            if let Some(iter) = &mut self.discovering {
-                if let Some(p) = iter.next() {
-                    self.nested.push(p.clone().into_iter());
-                    return Some(p.into());
-                }
-            }
-
-            if let Some(iter) = self.nested.pop() {
-                self.discovering = Some(iter);
-            } else {
-                break;
-            }
-        }
-
-        let i = self.iter.next();
-        self.discovering = i.clone().map(|pathable_schema| pathable_schema.into_iter());
-        i.map(|p| p.into())
+        self.iter.next().map(|(_, discovered)| discovered)
     }
 }
 
@@ -122,139 +259,268 @@ impl<'a> Iterator for SchemaDiscoverer<'a> {
 struct PathableSchema<'a> {
     root_path: String,
     path: String,
+    is_resource: bool,
     schema: &'a Schema,
 }
 
-impl<'a> IntoIterator for PathableSchema<'a> {
-    type Item = PathableSchema<'a>;
-    type IntoIter = std::vec::IntoIter<Self::Item>;
-
-    fn into_iter(self) -> Self::IntoIter {
-        let mut schemas: Vec<PathableSchema<'a>> = iter::empty()
-            .chain(self.properties())
-            .chain(self.items())
-            .chain(self.dependent_schemas())
-            .chain(self.pattern_properties())
-            .chain(self.definitions())
-            .collect();
-
-        schemas.sort_by(|left, right| String::cmp(&left.path, &right.path));
-        schemas.into_iter()
+/// Drops an empty fragment, which identifies the same schema as none.
+fn canonical(uri: String) -> String {
+    match uri.strip_suffix('#') {
+        Some(uri) => uri.to_owned(),
+        None => uri,
     }
 }
 
 impl<'a> PathableSchema<'a> {
-    fn definitions(&self) -> impl Iterator<Item = PathableSchema<'a>> + '_ {
-        self.schema
-            .definitions
-            .iter()
-            .flat_map(|map| map.iter())
-            .map(|(key, schema)| match &schema.get_id() {
-                Some(id) => PathableSchema {
-                    root_path: id.to_owned(),
-                    path: id.to_owned(),
-                    schema,
-                },
-                None => {
-                    let root_path = self.root_path.clone();
-                    let path = format!("{root_path}#/{DEFINITIONS_PATH}/{key}");
-                    PathableSchema {
-                        root_path,
-                        path,
-                        schema,
-                    }
-                }
-            })
+    /// A subschema with its own id starts a new root, resolved against the base URI of the root
+    /// it is nested in. The others are addressed by their JSON pointer from the closest root.
+    fn subschema(&self, position: Position<'_>, schema: &'a Schema) -> PathableSchema<'a> {
+        let id = schema
+            .get_id()
+            .map(|id| canonical(resolve_uri(&self.root_path, &id)))
+            // Draft 4 identifies plain name fragments with `id`, which don't start a new root.
+            .filter(|id| !id.contains('#'));
+
+        match id {
+            Some(id) => PathableSchema {
+                root_path: id.clone(),
+                path: id,
+                is_resource: true,
+                schema,
+            },
+            None => PathableSchema {
+                root_path: self.root_path.clone(),
+                path: append_to_path(&self.path, &position.to_pointer().to_string()[1..]),
+                is_resource: false,
+                schema,
+            },
+        }
     }
+}
 
-    fn properties(&self) -> impl Iterator<Item = PathableSchema<'a>> + '_ {
-        self.schema
-            .properties
-            .iter()
-            .flat_map(|map| map.iter())
-            .map({
-                let path = self.path.clone();
-                move |(key, schema)| {
-                    let path = match path.contains('#') {
-                        true => format!("{path}/{PROPERTIES_PATH}/{key}"),
-                        false => format!("{path}#/{PROPERTIES_PATH}/{key}"),
-                    };
-
-                    PathableSchema {
-                        root_path: self.root_path.clone(),
-                        path,
-                        schema,
-                    }
-                }
-            })
+/// Collects the subschemas, keeping track of the closest root and of the paths leading to each
+/// subschema on the way down.
+struct DiscoveryCollector<'a> {
+    current: PathableSchema<'a>,
+    ancestry: Vec<String>,
+    discovered: Vec<(Vec<String>, DiscoveredSchema<'a>)>,
+}
+
+// Boolean subschemas are never yielded, as they can neither be identified nor contain others.
+impl<'a> Visitor<'a> for DiscoveryCollector<'a> {
+    fn visit_subschema(
+        &mut self,
+        pointer: &JsonPointer,
+        position: Position<'a>,
+        subschema: &'a BooleanOrSchema,
+    ) {
+        let Some(schema) = subschema.as_schema() else {
+            return;
+        };
+
+        let subschema = self.current.subschema(position, schema);
+        let parent = std::mem::replace(&mut self.current, subschema.clone());
+
+        self.ancestry.push(subschema.path.clone());
+        self.discovered
+            .push((self.ancestry.clone(), subschema.into()));
+        walk_schema(self, pointer, schema);
+        self.ancestry.pop();
+        self.current = parent;
     }
+}
 
-    fn items(&self) -> impl Iterator<Item = PathableSchema<'a>> + '_ {
-        self.schema
-            .items
-            .iter()
-            .filter_map(|items| match items {
-                serde_json_schema::BooleanOrSchema::InnerSchema(schema) => Some(schema),
-                serde_json_schema::BooleanOrSchema::Boolean(_) => None,
-            })
-            .map({
-                let path = self.path.clone();
-                move |schema| {
-                    let path = match path.contains('#') {
-                        true => format!("{path}/{ITEMS_PATH}"),
-                        false => format!("{path}#/{ITEMS_PATH}"),
-                    };
-
-                    PathableSchema {
-                        root_path: self.root_path.clone(),
-                        path,
-                        schema,
-                    }
-                }
-            })
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use super::*;
+
+    fn discovered_ids(json: &str) -> BTreeSet<String> {
+        let schema: Schema = serde_json::from_str(json).unwrap();
+
+        schema
+            .discover()
+            .map(|discovered| discovered.id().to_owned())
+            .collect()
     }
 
-    fn dependent_schemas(&self) -> impl Iterator<Item = PathableSchema<'a>> + '_ {
-        self.schema
-            .dependent_schemas
-            .iter()
-            .flat_map(|map| map.iter())
-            .map({
-                let path = self.path.clone();
-                move |(key, schema)| {
-                    let path = match path.contains('#') {
-                        true => format!("{path}/{DEPENDENT_SCHEMAS_PATH}/{key}"),
-                        false => format!("{path}#/{DEPENDENT_SCHEMAS_PATH}/{key}"),
-                    };
-
-                    PathableSchema {
-                        root_path: self.root_path.clone(),
-                        path,
-                        schema,
-                    }
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-keywords-for-applying-subschema
+    #[test]
+    fn subschema_locations_example() {
+        let ids = discovered_ids(
+            r##"{
+                "$id": "https://example.com/root",
+                "$defs": { "a": {} },
+                "definitions": { "a": {} },
+                "properties": { "a/b": {} },
+                "patternProperties": { "^a": {} },
+                "additionalProperties": {},
+                "unevaluatedProperties": {},
+                "propertyNames": {},
+                "dependentSchemas": { "a": {} },
+                "dependencies": { "a": {}, "b": ["a"] },
+                "prefixItems": [{}, true],
+                "items": {},
+                "contains": {},
+                "unevaluatedItems": {},
+                "allOf": [{ "not": {} }],
+                "anyOf": [false, {}],
+                "oneOf": [{}],
+                "not": {},
+                "if": {},
+                "then": {},
+                "else": {},
+                "contentSchema": {}
+            }"##,
+        );
+
+        let expected = [
+            "#/$defs/a",
+            "#/definitions/a",
+            "#/properties/a~1b",
+            "#/patternProperties/^a",
+            "#/additionalProperties",
+            "#/unevaluatedProperties",
+            "#/propertyNames",
+            "#/dependentSchemas/a",
+            "#/dependencies/a",
+            "#/prefixItems/0",
+            "#/items",
+            "#/contains",
+            "#/unevaluatedItems",
+            "#/allOf/0",
+            "#/allOf/0/not",
+            "#/anyOf/1",
+            "#/oneOf/0",
+            "#/not",
+            "#/if",
+            "#/then",
+            "#/else",
+            "#/contentSchema",
+        ]
+        .map(|pointer| format!("https://example.com/root{pointer}"));
+
+        assert_eq!(ids, BTreeSet::from(expected));
+    }
+
+    #[test]
+    fn tuple_items_locations_example() {
+        let ids = discovered_ids(
+            r##"{
+                "$id": "https://example.com/tuple",
+                "items": [{}, {}],
+                "additionalItems": {}
+            }"##,
+        );
+
+        let expected = ["#/items/0", "#/items/1", "#/additionalItems"]
+            .map(|pointer| format!("https://example.com/tuple{pointer}"));
+
+        assert_eq!(ids, BTreeSet::from(expected));
+    }
+
+    /// https://www.rfc-editor.org/rfc/rfc3986#section-5.4
+    #[test]
+    fn reference_resolution_example() {
+        let base = "http://a/b/c/d;p?q";
+        let examples = [
+            ("g:h", "g:h"),
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            ("?y", "http://a/b/c/d;p?y"),
+            ("g?y", "http://a/b/c/g?y"),
+            ("#s", "http://a/b/c/d;p?q#s"),
+            ("g#s", "http://a/b/c/g#s"),
+            ("", "http://a/b/c/d;p?q"),
+            (".", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../..", "http://a/"),
+            ("../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("g/../h", "http://a/b/c/h"),
+            ("g;x=1/./y", "http://a/b/c/g;x=1/y"),
+        ];
+
+        for (reference, expected) in examples {
+            assert_eq!(resolve_uri(base, reference), expected, "{reference}");
+        }
+
+        assert_eq!(
+            resolve_uri("urn:example:root", "#/$defs/a"),
+            "urn:example:root#/$defs/a"
+        );
+    }
+
+    /// https://json-schema.org/understanding-json-schema/structuring#base-uri
+    #[test]
+    fn relative_identifier_example() {
+        let schema: Schema = serde_json::from_str(
+            r##"{
+                "$id": "https://example.com/schemas/person.json",
+                "properties": {
+                    "address": {
+                        "$id": "address.json",
+                        "properties": {
+                            "country": { "$id": "/shared/country.json#" }
+                        }
+                    },
+                    "name": { "$id": "#name-fragment" }
                 }
+            }"##,
+        )
+        .unwrap();
+
+        let discovered: Vec<(String, Option<String>)> = schema
+            .discover()
+            .map(|discovered| {
+                (
+                    discovered.id().to_owned(),
+                    discovered.canonical_uri().map(str::to_owned),
+                )
             })
+            .collect();
+
+        let resource = |uri: &str| (uri.to_owned(), Some(uri.to_owned()));
+
+        assert_eq!(
+            discovered,
+            vec![
+                resource("https://example.com/schemas/address.json"),
+                resource("https://example.com/shared/country.json"),
+                (
+                    "https://example.com/schemas/person.json#/properties/name".to_owned(),
+                    None
+                ),
+            ]
+        );
     }
 
-    fn pattern_properties(&self) -> impl Iterator<Item = PathableSchema<'a>> + '_ {
-        self.schema
-            .pattern_properties
-            .iter()
-            .flat_map(|map| map.iter())
-            .map({
-                let path = self.path.clone();
-                move |(key, schema)| {
-                    let path = match path.contains('#') {
-                        true => format!("{path}/{PATTERN_PROPERTIES_PATH}/{key}"),
-                        false => format!("{path}#/{PATTERN_PROPERTIES_PATH}/{key}"),
-                    };
-
-                    PathableSchema {
-                        root_path: self.root_path.clone(),
-                        path,
-                        schema,
+    #[test]
+    fn nested_identifier_example() {
+        let ids = discovered_ids(
+            r##"{
+                "$id": "https://example.com/root",
+                "anyOf": [
+                    {
+                        "$id": "https://example.com/nested",
+                        "$anchor": "nested",
+                        "if": {}
                     }
-                }
-            })
+                ]
+            }"##,
+        );
+
+        assert_eq!(
+            ids,
+            BTreeSet::from([
+                "https://example.com/nested".to_owned(),
+                "https://example.com/nested#/if".to_owned(),
+            ])
+        );
     }
 }
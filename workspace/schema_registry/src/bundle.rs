@@ -0,0 +1,372 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+
+use schema_discovery::resolve_uri;
+use serde_json_schema::{walk_schema, BooleanOrSchema, Dialect, JsonPointer, Schema, Visitor};
+
+use crate::{rebase, strip_empty_fragment, SchemaRegistry};
+
+/// A compound document holding a schema and every document it references, directly or not.
+#[derive(Debug, Clone)]
+pub struct Bundle {
+    schema: Schema,
+    documents: Vec<String>,
+    cycles: Vec<Vec<String>>,
+}
+
+impl Bundle {
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn into_schema(self) -> Schema {
+        self.schema
+    }
+
+    /// The documents embedded in the root schema, in the order they were first referenced.
+    pub fn documents(&self) -> &[String] {
+        &self.documents
+    }
+
+    /// The chains of documents referencing their way back to one of themselves, e.g.
+    /// `[order, customer, order]`. They need no special treatment in a bundle, but do in tools
+    /// that inline references.
+    pub fn cycles(&self) -> &[Vec<String>] {
+        &self.cycles
+    }
+}
+
+impl SchemaRegistry {
+    /// Bundles the schema with the id and every document it transitively references into one
+    /// compound document, as described in
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-bundling.
+    ///
+    /// The referenced documents are embedded in the `$defs` of the root, or its `definitions` in
+    /// drafts before 2019-09, keyed by their URI. Each keeps its `$id`, so that every reference
+    /// resolves as it did before without being rewritten; only documents whose `$id` doesn't
+    /// match the URI they are registered under get it set to that URI.
+    pub fn bundle(&self, root_id: &str) -> Result<Bundle, BundleError> {
+        let root = self
+            .resolve("", root_id)
+            .ok_or_else(|| BundleError::UnknownSchema(root_id.to_owned()))?;
+
+        let mut bundler = Bundler {
+            registry: self,
+            stack: Vec::new(),
+            resources: HashMap::new(),
+            embedded: Vec::new(),
+            cycles: Vec::new(),
+        };
+        bundler.include(root.base_uri().to_owned(), root.schema())?;
+
+        let mut schema = root.schema().clone();
+        let legacy = schema
+            .dialect()
+            .is_some_and(|dialect| dialect <= Dialect::Draft7);
+        let definitions = match legacy {
+            true => &mut schema.legacy_definitions,
+            false => &mut schema.definitions,
+        };
+
+        let documents = bundler
+            .embedded
+            .iter()
+            .map(|(uri, _)| uri.clone())
+            .collect();
+
+        if !bundler.embedded.is_empty() {
+            let definitions = definitions.get_or_insert_with(HashMap::new);
+
+            for (uri, document) in bundler.embedded {
+                definitions.insert(uri, BooleanOrSchema::InnerSchema(Box::new(document)));
+            }
+        }
+
+        Ok(Bundle {
+            schema,
+            documents,
+            cycles: bundler.cycles,
+        })
+    }
+}
+
+struct Bundler<'r> {
+    registry: &'r SchemaRegistry,
+    /// The documents being bundled, from the root down to the current one.
+    stack: Vec<String>,
+    /// The base URI of every schema resource in the bundle so far, with the document it is in.
+    resources: HashMap<String, String>,
+    embedded: Vec<(String, Schema)>,
+    cycles: Vec<Vec<String>>,
+}
+
+impl Bundler<'_> {
+    fn include(&mut self, uri: String, document: &Schema) -> Result<(), BundleError> {
+        let mut references = References {
+            bases: vec![uri.clone()],
+            resources: vec![uri.clone()],
+            references: Vec::new(),
+        };
+        references.visit_schema(&JsonPointer::root(), document);
+
+        for resource in references.resources {
+            self.resources
+                .entry(resource)
+                .or_insert_with(|| uri.clone());
+        }
+
+        self.stack.push(uri.clone());
+
+        for reference in references.references {
+            let target = reference
+                .split_once('#')
+                .map_or(&*reference, |(document, _)| document);
+
+            if let Some(owner) = self.resources.get(target) {
+                if let Some(index) = self.stack.iter().position(|document| document == owner) {
+                    if *owner != uri {
+                        let mut cycle = self.stack[index..].to_vec();
+                        cycle.push(owner.clone());
+                        self.cycles.push(cycle);
+                    }
+                }
+
+                continue;
+            }
+
+            let resolved = self
+                .registry
+                .resolve("", &reference)
+                .and_then(|_| self.registry.resolve("", target))
+                .ok_or_else(|| {
+                    BundleError::UnresolvableReference(uri.clone(), reference.clone())
+                })?;
+
+            let mut embedded = resolved.schema().clone();
+            let id = embedded
+                .get_id()
+                .filter(|id| !id.starts_with('#'))
+                .map(|id| strip_empty_fragment(resolve_uri(target, &id)));
+
+            if id.as_deref() != Some(target) {
+                match embedded.id.is_some() {
+                    true => embedded.id = Some(target.to_owned()),
+                    false => embedded.dollar_id = Some(target.to_owned()),
+                }
+            }
+
+            self.embedded.push((target.to_owned(), embedded));
+            self.include(target.to_owned(), resolved.schema())?;
+        }
+
+        self.stack.pop();
+        Ok(())
+    }
+}
+
+/// Collects the schema resources in a document and the absolute URIs of its references.
+struct References {
+    bases: Vec<String>,
+    resources: Vec<String>,
+    references: Vec<String>,
+}
+
+impl<'a> Visitor<'a> for References {
+    fn visit_schema(&mut self, pointer: &JsonPointer, schema: &'a Schema) {
+        let base = rebase(self.bases.last().map_or("", String::as_str), schema);
+
+        if schema.get_id().is_some_and(|id| !id.starts_with('#')) {
+            self.resources.push(base.clone());
+        }
+
+        let references = [
+            &schema.reference,
+            &schema.dynamic_reference,
+            &schema.recursive_reference,
+        ];
+        for reference in references.into_iter().flatten() {
+            self.references
+                .push(strip_empty_fragment(resolve_uri(&base, reference)));
+        }
+
+        self.bases.push(base);
+        walk_schema(self, pointer, schema);
+        self.bases.pop();
+    }
+}
+
+#[derive(Debug)]
+pub enum BundleError {
+    UnknownSchema(String),
+    UnresolvableReference(String, String),
+}
+
+impl Error for BundleError {}
+
+impl Display for BundleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownSchema(id) => write!(f, "There is no schema with the id {id} to bundle."),
+            Self::UnresolvableReference(document, reference) => write!(
+                f,
+                "The reference {reference} in {document} is not resolvable."
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn schema(value: serde_json::Value) -> Schema {
+        Schema::from_value_with_dialect(value, Dialect::Draft2020_12).unwrap()
+    }
+
+    /// https://json-schema.org/blog/posts/bundling-json-schema-compound-documents
+    #[test]
+    fn bundle_example() {
+        let order = schema(json!({
+            "$id": "https://example.com/order.schema.json",
+            "properties": {
+                "customer": { "$ref": "customer.schema.json" },
+                "lines": { "type": "array", "items": { "$ref": "#/$defs/line" } }
+            },
+            "$defs": {
+                "line": {
+                    "properties": { "sku": { "$ref": "product.schema.json#/$defs/sku" } }
+                }
+            }
+        }));
+        let customer = schema(json!({
+            "$id": "https://example.com/customer.schema.json",
+            "properties": {
+                "address": { "$ref": "common/address.schema.json" },
+                "orders": { "type": "array", "items": { "$ref": "order.schema.json" } }
+            }
+        }));
+        let address = schema(json!({
+            "$id": "https://example.com/common/address.schema.json",
+            "title": "Address"
+        }));
+        let product = schema(json!({
+            "$defs": { "sku": { "type": "string" } }
+        }));
+
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(order)
+            .unwrap()
+            .add_internally_identified_schema(customer)
+            .unwrap()
+            .add_internally_identified_schema(address)
+            .unwrap()
+            .add_externally_referenced_schema(
+                "https://example.com/product.schema.json".to_owned(),
+                product,
+            )
+            .unwrap();
+
+        let bundle = registry
+            .bundle("https://example.com/order.schema.json")
+            .unwrap();
+
+        assert_eq!(
+            bundle.documents(),
+            [
+                "https://example.com/product.schema.json",
+                "https://example.com/customer.schema.json",
+                "https://example.com/common/address.schema.json",
+            ]
+        );
+        assert_eq!(
+            bundle.cycles(),
+            [vec![
+                "https://example.com/order.schema.json".to_owned(),
+                "https://example.com/customer.schema.json".to_owned(),
+                "https://example.com/order.schema.json".to_owned(),
+            ]]
+        );
+
+        let definitions = bundle.schema().definitions.as_ref().unwrap();
+        let customer = definitions["https://example.com/customer.schema.json"]
+            .as_schema()
+            .unwrap();
+        let product = definitions["https://example.com/product.schema.json"]
+            .as_schema()
+            .unwrap();
+
+        assert_eq!(
+            customer.properties.as_ref().unwrap()["address"]
+                .as_schema()
+                .unwrap()
+                .reference
+                .as_deref(),
+            Some("common/address.schema.json")
+        );
+        assert_eq!(
+            product.dollar_id.as_deref(),
+            Some("https://example.com/product.schema.json")
+        );
+
+        let bundled = SchemaRegistry::new()
+            .add_internally_identified_schema(bundle.into_schema())
+            .unwrap()
+            .discover()
+            .unwrap();
+        let references = [
+            (
+                "https://example.com/order.schema.json",
+                "customer.schema.json",
+            ),
+            (
+                "https://example.com/order.schema.json",
+                "product.schema.json#/$defs/sku",
+            ),
+            (
+                "https://example.com/customer.schema.json",
+                "common/address.schema.json",
+            ),
+            (
+                "https://example.com/customer.schema.json",
+                "order.schema.json",
+            ),
+        ];
+
+        for (base, reference) in references {
+            assert!(bundled.resolve(base, reference).is_some(), "{reference}");
+        }
+    }
+
+    #[test]
+    fn bundle_errors_example() {
+        let catalog = schema(json!({
+            "$id": "https://example.com/catalog.schema.json",
+            "items": { "$ref": "product.schema.json" }
+        }));
+        let product = schema(json!({
+            "$id": "https://example.com/product.schema.json",
+            "properties": { "vendor": { "$ref": "vendor.schema.json" } }
+        }));
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(catalog)
+            .unwrap()
+            .add_internally_identified_schema(product)
+            .unwrap();
+
+        assert!(matches!(
+            registry.bundle("https://example.com/missing.schema.json"),
+            Err(BundleError::UnknownSchema(_))
+        ));
+
+        match registry.bundle("https://example.com/catalog.schema.json") {
+            Err(BundleError::UnresolvableReference(document, reference)) => {
+                assert_eq!(document, "https://example.com/product.schema.json");
+                assert_eq!(reference, "https://example.com/vendor.schema.json");
+            }
+            other => panic!("{other:?}"),
+        }
+    }
+}
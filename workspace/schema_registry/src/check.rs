@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use serde_json_schema::{walk_schema, JsonPointer, Schema, Visitor};
+
+use crate::{rebase, SchemaRegistry};
+
+/// Where a schema is, as the id of the registered document it is in and the JSON pointer to it
+/// from the document's root.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+    document: String,
+    pointer: JsonPointer,
+}
+
+impl Location {
+    pub fn document(&self) -> &str {
+        &self.document
+    }
+
+    pub fn pointer(&self) -> &JsonPointer {
+        &self.pointer
+    }
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}{}", self.document, self.pointer.to_fragment())
+    }
+}
+
+/// A problem [`SchemaRegistry::check`] found, at the schema it is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    location: Location,
+    kind: DiagnosticKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiagnosticKind {
+    /// A `$ref`, `$dynamicRef` or `$recursiveRef`, as written, that resolves to nothing.
+    UnresolvableReference(String),
+    /// An `$id` resolving to the same URI as the schema at the location.
+    DuplicateId(String, Location),
+    /// An anchor with the same URI as the one at the location, which is the one references get.
+    DuplicateAnchor(String, Location),
+    /// An anchor whose URI is the id of a schema registered on its own, which is the one
+    /// references get.
+    ShadowedAnchor(String),
+}
+
+impl Diagnostic {
+    pub fn location(&self) -> &Location {
+        &self.location
+    }
+
+    pub fn kind(&self) -> &DiagnosticKind {
+        &self.kind
+    }
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.kind {
+            DiagnosticKind::UnresolvableReference(reference) => write!(
+                f,
+                "{}: The reference {reference} is not resolvable.",
+                self.location
+            ),
+            DiagnosticKind::DuplicateId(id, first) => write!(
+                f,
+                "{}: The id {id} is already used at {first}.",
+                self.location
+            ),
+            DiagnosticKind::DuplicateAnchor(anchor, first) => write!(
+                f,
+                "{}: The anchor {anchor} is already declared at {first}.",
+                self.location
+            ),
+            DiagnosticKind::ShadowedAnchor(anchor) => write!(
+                f,
+                "{}: The anchor {anchor} is shadowed by the schema registered with that id.",
+                self.location
+            ),
+        }
+    }
+}
+
+impl SchemaRegistry {
+    /// Checks every registered document for references that don't resolve, ids and anchors
+    /// declared more than once, and anchors shadowed by a registered schema. Unlike
+    /// [`SchemaRegistry::discover`], which stops at the first duplicate, it reports every problem
+    /// it finds, ordered by kind and then by document.
+    pub fn check(&self) -> Vec<Diagnostic> {
+        let mut documents = self.schemas.iter().collect::<Vec<(&String, &Schema)>>();
+        documents.sort_by_key(|(id, _)| *id);
+
+        let mut collector = Collector {
+            registry: self,
+            document: String::new(),
+            bases: Vec::new(),
+            resources: Vec::new(),
+            anchors: Vec::new(),
+            unresolvable: Vec::new(),
+        };
+
+        for (id, document) in documents {
+            collector.document = id.clone();
+            collector.bases.push(id.clone());
+            collector
+                .resources
+                .push((id.clone(), collector.location(&JsonPointer::root())));
+            collector.visit_schema(&JsonPointer::root(), document);
+            collector.bases.pop();
+        }
+
+        let mut diagnostics = collector.unresolvable;
+        diagnostics.extend(duplicates(collector.resources, DiagnosticKind::DuplicateId));
+        diagnostics.extend(duplicates(
+            collector.anchors.clone(),
+            DiagnosticKind::DuplicateAnchor,
+        ));
+        diagnostics.extend(
+            collector
+                .anchors
+                .into_iter()
+                .filter(|(anchor, _)| self.schemas.contains_key(anchor))
+                .map(|(anchor, location)| Diagnostic {
+                    location,
+                    kind: DiagnosticKind::ShadowedAnchor(anchor),
+                }),
+        );
+
+        diagnostics
+    }
+}
+
+/// Every declaration of a URI after the first, pointing back at the first.
+fn duplicates(
+    declarations: Vec<(String, Location)>,
+    kind: fn(String, Location) -> DiagnosticKind,
+) -> Vec<Diagnostic> {
+    let mut first: HashMap<String, Location> = HashMap::new();
+    let mut diagnostics = Vec::new();
+
+    for (uri, location) in declarations {
+        match first.get(&uri) {
+            Some(first) if *first != location => diagnostics.push(Diagnostic {
+                location,
+                kind: kind(uri, first.clone()),
+            }),
+            Some(_) => {}
+            None => {
+                first.insert(uri, location);
+            }
+        }
+    }
+
+    diagnostics
+}
+
+/// Collects the declarations of ids and anchors in the registered documents, and the references
+/// in them that don't resolve.
+struct Collector<'r> {
+    registry: &'r SchemaRegistry,
+    document: String,
+    /// The base URI of each schema on the way to the current one.
+    bases: Vec<String>,
+    resources: Vec<(String, Location)>,
+    anchors: Vec<(String, Location)>,
+    unresolvable: Vec<Diagnostic>,
+}
+
+impl Collector<'_> {
+    fn location(&self, pointer: &JsonPointer) -> Location {
+        Location {
+            document: self.document.clone(),
+            pointer: pointer.clone(),
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for Collector<'_> {
+    fn visit_schema(&mut self, pointer: &JsonPointer, schema: &'a Schema) {
+        let parent_base = self.bases.last().cloned().unwrap_or_default();
+        let base_uri = rebase(&parent_base, schema);
+        let id = schema.get_id();
+
+        match id.as_deref().and_then(|id| id.strip_prefix('#')) {
+            // Draft 4 and 6 declare plain name fragments with `id` or `$id`.
+            Some(name) if !name.is_empty() => {
+                let anchor = format!("{base_uri}#{name}");
+                self.anchors.push((anchor, self.location(pointer)));
+            }
+            Some(_) => {}
+            None if id.is_some() && !pointer.is_root() => {
+                self.resources
+                    .push((base_uri.clone(), self.location(pointer)));
+            }
+            None => {}
+        }
+
+        let mut names = [&schema.anchor, &schema.dynamic_anchor]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<&String>>();
+        names.dedup();
+
+        for name in names {
+            let anchor = format!("{base_uri}#{name}");
+            self.anchors.push((anchor, self.location(pointer)));
+        }
+
+        let references = [
+            &schema.reference,
+            &schema.dynamic_reference,
+            &schema.recursive_reference,
+        ];
+        for reference in references.into_iter().flatten() {
+            if self.registry.resolve(&base_uri, reference).is_none() {
+                self.unresolvable.push(Diagnostic {
+                    location: self.location(pointer),
+                    kind: DiagnosticKind::UnresolvableReference(reference.clone()),
+                });
+            }
+        }
+
+        self.bases.push(base_uri);
+        walk_schema(self, pointer, schema);
+        self.bases.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn location(document: &str, pointer: &str) -> Location {
+        Location {
+            document: document.to_owned(),
+            pointer: pointer.parse().unwrap(),
+        }
+    }
+
+    #[test]
+    fn check_example() {
+        let order: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/order",
+            "properties": {
+                "customer": { "$ref": "customer" },
+                "product": { "$ref": "product" },
+                "lines": { "items": { "$ref": "#line" } },
+                "total": { "$ref": "#/$defs/total" },
+                "parts": { "items": { "$recursiveRef": "part" } }
+            },
+            "$defs": {
+                "customer": { "$id": "customer", "title": "Customer" },
+                "line": { "$anchor": "line" },
+                "other-line": { "$anchor": "line" }
+            }
+        }))
+        .unwrap();
+        let customer: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/customer",
+            "$defs": { "address": { "$anchor": "address" } }
+        }))
+        .unwrap();
+        let address: Schema = serde_json::from_value(json!({ "title": "Address" })).unwrap();
+
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(order)
+            .unwrap()
+            .add_internally_identified_schema(customer)
+            .unwrap()
+            .add_externally_referenced_schema(
+                "https://example.com/customer#address".to_owned(),
+                address,
+            )
+            .unwrap();
+
+        let order = "https://example.com/order";
+        let customer = "https://example.com/customer";
+
+        assert_eq!(
+            registry.check(),
+            [
+                Diagnostic {
+                    location: location(order, "/properties/parts/items"),
+                    kind: DiagnosticKind::UnresolvableReference("part".to_owned()),
+                },
+                Diagnostic {
+                    location: location(order, "/properties/product"),
+                    kind: DiagnosticKind::UnresolvableReference("product".to_owned()),
+                },
+                Diagnostic {
+                    location: location(order, "/properties/total"),
+                    kind: DiagnosticKind::UnresolvableReference("#/$defs/total".to_owned()),
+                },
+                Diagnostic {
+                    location: location(order, "/$defs/customer"),
+                    kind: DiagnosticKind::DuplicateId(customer.to_owned(), location(customer, "")),
+                },
+                Diagnostic {
+                    location: location(order, "/$defs/other-line"),
+                    kind: DiagnosticKind::DuplicateAnchor(
+                        "https://example.com/order#line".to_owned(),
+                        location(order, "/$defs/line")
+                    ),
+                },
+                Diagnostic {
+                    location: location(customer, "/$defs/address"),
+                    kind: DiagnosticKind::ShadowedAnchor(
+                        "https://example.com/customer#address".to_owned()
+                    ),
+                },
+            ]
+        );
+        assert_eq!(
+            registry.check()[3].to_string(),
+            "https://example.com/order#/$defs/customer: The id https://example.com/customer is \
+             already used at https://example.com/customer#."
+        );
+    }
+}
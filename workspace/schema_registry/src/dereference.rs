@@ -0,0 +1,635 @@
+use std::error::Error;
+use std::fmt::Display;
+
+use schema_discovery::resolve_uri;
+use serde_json::Value;
+use serde_json_schema::{walk_schema_mut, Dialect, JsonPointer, Schema, VisitorMut};
+
+use crate::{rebase, strip_empty_fragment, DynamicScope, ResolvedSchema, SchemaRegistry};
+
+/// Keywords whose meaning depends on each other within a schema, so that a `$ref` target and its
+/// siblings can't be merged into one schema when both use the same group.
+const ADJACENT_KEYWORDS: [&[&str]; 6] = [
+    &[
+        "properties",
+        "patternProperties",
+        "additionalProperties",
+        "unevaluatedProperties",
+    ],
+    &[
+        "prefixItems",
+        "items",
+        "additionalItems",
+        "unevaluatedItems",
+    ],
+    &["contains", "minContains", "maxContains"],
+    &["if", "then", "else"],
+    &["minimum", "exclusiveMinimum"],
+    &["maximum", "exclusiveMaximum"],
+];
+
+/// Replaces references with the schemas they resolve to, for tools that can't follow them.
+pub struct Dereferencer<'r> {
+    registry: &'r SchemaRegistry,
+    max_depth: Option<usize>,
+    default_dialect: Dialect,
+}
+
+impl<'r> Dereferencer<'r> {
+    pub fn new(registry: &'r SchemaRegistry) -> Self {
+        Dereferencer {
+            registry,
+            max_depth: None,
+            default_dialect: Dialect::Draft2020_12,
+        }
+    }
+
+    /// Expands a recursive reference at most this many times along any path, then leaves it as a
+    /// reference with an absolute URI. Without a depth, recursion is a
+    /// [`DereferenceError::Cycle`].
+    pub fn max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = Some(depth);
+        self
+    }
+
+    /// The dialect of schemas that don't declare one with `$schema`, which decides whether the
+    /// keywords next to a `$ref` apply.
+    pub fn default_dialect(mut self, dialect: Dialect) -> Self {
+        self.default_dialect = dialect;
+        self
+    }
+
+    /// The schema with the id, with every `$ref`, `$dynamicRef` and `$recursiveRef` in it
+    /// replaced by what it resolves to.
+    ///
+    /// From 2019-09 on, the keywords next to a reference are merged into the referenced schema,
+    /// or combined with it in an `allOf` when merging would change what they mean. In earlier
+    /// drafts they are ignored, as they are in validation.
+    pub fn dereference(&self, id: &str) -> Result<Schema, DereferenceError> {
+        let root = self
+            .registry
+            .resolve("", id)
+            .ok_or_else(|| DereferenceError::UnknownSchema(id.to_owned()))?;
+        let dialect = root.schema().dialect().unwrap_or(self.default_dialect);
+
+        let mut inliner = Inliner {
+            dereferencer: self,
+            document: root.schema(),
+            bases: Vec::new(),
+            path: vec![(id.to_owned(), location(self.registry, id, &root))],
+            scope: DynamicScope::new(),
+            error: None,
+        };
+        let schema = inliner.inline(&root, dialect, false);
+
+        match inliner.error {
+            Some(error) => Err(error),
+            None => Ok(schema),
+        }
+    }
+}
+
+/// The keyword a reference is in, which decides how it is resolved.
+#[derive(Clone, Copy)]
+enum ReferenceKind {
+    Static,
+    Dynamic,
+    Recursive,
+}
+
+struct Inliner<'d, 'r> {
+    dereferencer: &'d Dereferencer<'r>,
+    /// The registry schema being inlined, which the pointers visited are relative to.
+    document: &'r Schema,
+    /// The base URI and dialect of each schema from the document down to the current one.
+    bases: Vec<(String, Dialect)>,
+    /// The references expanded on the way to the current schema, with the canonical location of
+    /// the schemas they resolved to.
+    path: Vec<(String, String)>,
+    scope: DynamicScope<'r>,
+    error: Option<DereferenceError>,
+}
+
+impl<'r> Inliner<'_, 'r> {
+    /// A copy of the schema with its references expanded. Referenced schemas lose what identifies
+    /// them and their definitions, which they no longer need.
+    fn inline(
+        &mut self,
+        target: &ResolvedSchema<'r>,
+        dialect: Dialect,
+        referenced: bool,
+    ) -> Schema {
+        let document = std::mem::replace(&mut self.document, target.schema());
+        let mut schema = target.schema().clone();
+
+        if referenced {
+            schema.definitions = None;
+            schema.legacy_definitions = None;
+        }
+
+        self.bases.push((target.base_uri().to_owned(), dialect));
+        self.visit_schema_mut(&JsonPointer::root(), &mut schema);
+        self.bases.pop();
+        self.document = document;
+
+        if referenced {
+            schema.dollar_id = None;
+            schema.id = None;
+            schema.anchor = None;
+            schema.dynamic_anchor = None;
+            schema.recursive_anchor = None;
+            schema.vocabulary = None;
+
+            if schema.dialect() == Some(dialect) {
+                schema.schema = None;
+            }
+        }
+
+        schema
+    }
+
+    fn expand(
+        &mut self,
+        schema: &mut Schema,
+        reference: String,
+        kind: ReferenceKind,
+        base_uri: &str,
+        dialect: Dialect,
+    ) {
+        let registry = self.dereferencer.registry;
+        let uri = strip_empty_fragment(resolve_uri(base_uri, &reference));
+        let target = match kind {
+            ReferenceKind::Static => registry.resolve(base_uri, &reference),
+            ReferenceKind::Dynamic => registry.resolve_dynamic(base_uri, &reference, &self.scope),
+            ReferenceKind::Recursive => registry
+                .resolve(base_uri, &reference)
+                .map(|target| self.scope.resolve_recursive(target)),
+        };
+
+        let Some(target) = target else {
+            self.error = Some(DereferenceError::UnresolvableReference(uri));
+            return;
+        };
+
+        let location = location(registry, &uri, &target);
+        let expansions = self
+            .path
+            .iter()
+            .filter(|(_, expanded)| *expanded == location)
+            .count();
+
+        if expansions > 0 {
+            match self.dereferencer.max_depth {
+                Some(depth) if expansions > depth => {
+                    match kind {
+                        ReferenceKind::Static => schema.reference = Some(uri),
+                        ReferenceKind::Dynamic => schema.dynamic_reference = Some(uri),
+                        ReferenceKind::Recursive => schema.recursive_reference = Some(uri),
+                    }
+                    return;
+                }
+                Some(_) => {}
+                None => {
+                    let start = self
+                        .path
+                        .iter()
+                        .position(|(_, expanded)| *expanded == location)
+                        .unwrap_or_default();
+                    let mut cycle = self.path[start..]
+                        .iter()
+                        .map(|(uri, _)| uri.clone())
+                        .collect::<Vec<String>>();
+                    cycle.push(uri);
+
+                    self.error = Some(DereferenceError::Cycle(cycle));
+                    return;
+                }
+            }
+        }
+
+        self.path.push((uri, location));
+        let target_dialect = target.schema().dialect().unwrap_or(dialect);
+        let inlined = self.inline(&target, target_dialect, true);
+        self.path.pop();
+
+        *schema = match dialect >= Dialect::Draft2019_09 {
+            true => merge(inlined, schema.clone()),
+            false => inlined,
+        };
+    }
+}
+
+impl VisitorMut for Inliner<'_, '_> {
+    fn visit_schema_mut(&mut self, pointer: &JsonPointer, schema: &mut Schema) {
+        if self.error.is_some() {
+            return;
+        }
+
+        let Some(original) = self.document.pointer(pointer) else {
+            return;
+        };
+        let (parent_base, parent_dialect) = self
+            .bases
+            .last()
+            .cloned()
+            .expect("the document's base is pushed before it is visited");
+        let base_uri = rebase(&parent_base, original);
+        let dialect = original.dialect().unwrap_or(parent_dialect);
+
+        let is_resource = original.get_id().is_some_and(|id| !id.starts_with('#'))
+            || (pointer.is_root() && self.scope.resources().is_empty());
+        if is_resource {
+            self.scope.enter(original, base_uri.clone());
+        }
+
+        self.bases.push((base_uri.clone(), dialect));
+        walk_schema_mut(self, pointer, schema);
+        self.bases.pop();
+
+        if let Some(reference) = schema.reference.take() {
+            self.expand(schema, reference, ReferenceKind::Static, &base_uri, dialect);
+        }
+        if let Some(reference) = schema.dynamic_reference.take() {
+            self.expand(
+                schema,
+                reference,
+                ReferenceKind::Dynamic,
+                &base_uri,
+                dialect,
+            );
+        }
+        if let Some(reference) = schema.recursive_reference.take() {
+            self.expand(
+                schema,
+                reference,
+                ReferenceKind::Recursive,
+                &base_uri,
+                dialect,
+            );
+        }
+
+        if is_resource {
+            self.scope.leave();
+        }
+    }
+}
+
+/// Where the schema a reference resolved to is, independent of how it was reached: the URI of its
+/// schema resource, followed by its anchor or its JSON pointer within the resource. The registry
+/// keeps a copy of a schema for every id and anchor discovered in it, so the address of the
+/// schema tells nothing.
+fn location(registry: &SchemaRegistry, uri: &str, target: &ResolvedSchema<'_>) -> String {
+    let schema = target.schema();
+    let base_uri = target.base_uri();
+
+    if schema.get_id().is_some_and(|id| !id.starts_with('#')) {
+        return base_uri.to_owned();
+    }
+
+    let anchor = schema
+        .anchor
+        .clone()
+        .or_else(|| schema.dynamic_anchor.clone())
+        .or_else(|| schema.get_id()?.strip_prefix('#').map(str::to_owned));
+    if let Some(anchor) = anchor.filter(|anchor| !anchor.is_empty()) {
+        return format!("{base_uri}#{anchor}");
+    }
+
+    let (document, fragment) = uri.split_once('#').unwrap_or((uri, ""));
+    let (Some(root), Ok(pointer)) = (registry.get(document), JsonPointer::from_fragment(fragment))
+    else {
+        return uri.to_owned();
+    };
+
+    // The pointer may pass through schemas with their own id, which the rest of it is relative to.
+    let tokens = pointer.tokens().collect::<Vec<&str>>();
+    let start = (1..tokens.len())
+        .rev()
+        .find(|length| {
+            root.pointer(&tokens[..*length].iter().copied().collect())
+                .and_then(Schema::get_id)
+                .is_some_and(|id| !id.starts_with('#'))
+        })
+        .unwrap_or_default();
+    let pointer = tokens[start..].iter().copied().collect::<JsonPointer>();
+
+    match pointer.is_root() {
+        true => base_uri.to_owned(),
+        false => format!("{base_uri}{}", pointer.to_fragment()),
+    }
+}
+
+/// Merges the keywords next to a reference into the schema it resolved to, unless they overlap
+/// in a way that would change their meaning, in which case the resolved schema goes in an
+/// `allOf` instead.
+fn merge(target: Schema, siblings: Schema) -> Schema {
+    let (Ok(Value::Object(mut merged)), Ok(Value::Object(keywords))) = (
+        serde_json::to_value(&target),
+        serde_json::to_value(&siblings),
+    ) else {
+        return combine(target, siblings);
+    };
+
+    if keywords.is_empty() {
+        return target;
+    }
+
+    let overlaps = keywords.keys().any(|keyword| merged.contains_key(keyword))
+        || merged.contains_key("unevaluatedProperties")
+        || merged.contains_key("unevaluatedItems")
+        || ADJACENT_KEYWORDS.iter().any(|group| {
+            group.iter().any(|keyword| merged.contains_key(*keyword))
+                && group.iter().any(|keyword| keywords.contains_key(*keyword))
+        });
+
+    if overlaps {
+        return combine(target, siblings);
+    }
+
+    merged.extend(keywords);
+    serde_json::from_value(Value::Object(merged)).unwrap_or_else(|_| combine(target, siblings))
+}
+
+fn combine(target: Schema, mut siblings: Schema) -> Schema {
+    siblings
+        .all_of
+        .get_or_insert_with(Vec::new)
+        .insert(0, target.into());
+    siblings
+}
+
+#[derive(Debug)]
+pub enum DereferenceError {
+    UnknownSchema(String),
+    UnresolvableReference(String),
+    /// The references leading from a schema back to itself, starting with the one that first
+    /// led to it.
+    Cycle(Vec<String>),
+}
+
+impl Error for DereferenceError {}
+
+impl Display for DereferenceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnknownSchema(id) => {
+                write!(f, "There is no schema with the id {id} to dereference.")
+            }
+            Self::UnresolvableReference(reference) => {
+                write!(f, "The reference {reference} is not resolvable.")
+            }
+            Self::Cycle(path) => write!(
+                f,
+                "The references {} form a cycle, which can't be inlined.",
+                path.join(" -> ")
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn registry(schemas: impl IntoIterator<Item = Value>) -> SchemaRegistry {
+        schemas
+            .into_iter()
+            .fold(SchemaRegistry::new(), |registry, schema| {
+                let schema =
+                    Schema::from_value_with_dialect(schema, Dialect::Draft2020_12).unwrap();
+                registry.add_internally_identified_schema(schema).unwrap()
+            })
+    }
+
+    fn to_value(schema: Schema) -> Value {
+        serde_json::to_value(schema).unwrap()
+    }
+
+    #[test]
+    fn dereference_example() {
+        let registry = registry([
+            json!({
+                "$id": "https://example.com/order.schema.json",
+                "properties": {
+                    "product": { "$ref": "product.schema.json", "description": "The product" },
+                    "shipping": {
+                        "$ref": "#/$defs/address",
+                        "properties": { "company": { "type": "string" } }
+                    }
+                },
+                "$defs": {
+                    "address": {
+                        "properties": { "street": { "type": "string" } },
+                        "additionalProperties": false
+                    }
+                }
+            }),
+            json!({
+                "$id": "https://example.com/product.schema.json",
+                "$anchor": "product",
+                "type": "object",
+                "properties": { "name": { "type": "string" } }
+            }),
+            json!({
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "$id": "https://example.com/legacy.schema.json",
+                "properties": {
+                    "product": { "$ref": "product.schema.json", "description": "Ignored" }
+                }
+            }),
+        ]);
+        let dereferencer = Dereferencer::new(&registry);
+
+        let order = dereferencer
+            .dereference("https://example.com/order.schema.json")
+            .unwrap();
+
+        assert_eq!(
+            to_value(order)["properties"],
+            json!({
+                "product": {
+                    "type": "object",
+                    "description": "The product",
+                    "properties": { "name": { "type": "string" } }
+                },
+                "shipping": {
+                    "allOf": [{
+                        "properties": { "street": { "type": "string" } },
+                        "additionalProperties": false
+                    }],
+                    "properties": { "company": { "type": "string" } }
+                }
+            })
+        );
+
+        let legacy = dereferencer
+            .dereference("https://example.com/legacy.schema.json")
+            .unwrap();
+
+        assert_eq!(
+            to_value(legacy)["properties"]["product"],
+            json!({
+                "type": "object",
+                "properties": { "name": { "type": "string" } }
+            })
+        );
+
+        assert!(matches!(
+            dereferencer.dereference("https://example.com/missing.schema.json"),
+            Err(DereferenceError::UnknownSchema(_))
+        ));
+    }
+
+    #[test]
+    fn recursive_dereference_example() {
+        let registry = registry([
+            json!({
+                "$id": "https://example.com/tree",
+                "$dynamicAnchor": "node",
+                "type": "object",
+                "properties": {
+                    "data": true,
+                    "children": { "type": "array", "items": { "$dynamicRef": "#node" } }
+                }
+            }),
+            json!({
+                "$id": "https://example.com/strict-tree",
+                "$dynamicAnchor": "node",
+                "$ref": "tree",
+                "unevaluatedProperties": false
+            }),
+        ]);
+
+        match Dereferencer::new(&registry).dereference("https://example.com/strict-tree") {
+            Err(DereferenceError::Cycle(path)) => assert_eq!(
+                path,
+                [
+                    "https://example.com/strict-tree",
+                    "https://example.com/tree",
+                    "https://example.com/tree#node",
+                ]
+            ),
+            other => panic!("{other:?}"),
+        }
+
+        let strict_tree = Dereferencer::new(&registry)
+            .max_depth(1)
+            .dereference("https://example.com/strict-tree")
+            .unwrap();
+        let children = json!({
+            "type": "array",
+            "items": { "$dynamicRef": "https://example.com/tree#node" }
+        });
+
+        assert_eq!(
+            to_value(strict_tree),
+            json!({
+                "$id": "https://example.com/strict-tree",
+                "$dynamicAnchor": "node",
+                "allOf": [{
+                    "type": "object",
+                    "properties": {
+                        "data": true,
+                        "children": {
+                            "type": "array",
+                            "items": {
+                                "allOf": [{
+                                    "type": "object",
+                                    "properties": { "data": true, "children": children }
+                                }],
+                                "unevaluatedProperties": false
+                            }
+                        }
+                    }
+                }],
+                "unevaluatedProperties": false
+            })
+        );
+
+        // Discovery registers a copy of `node` under its anchor, which is still the same schema.
+        let list = json!({
+            "$id": "https://example.com/list",
+            "properties": { "head": { "$ref": "#/$defs/node" } },
+            "$defs": {
+                "node": { "$anchor": "node", "properties": { "next": { "$ref": "#node" } } }
+            }
+        });
+        let head = json!({
+            "properties": {
+                "next": {
+                    "properties": { "next": { "$ref": "https://example.com/list#node" } }
+                }
+            }
+        });
+
+        let undiscovered = self::registry([list.clone()]);
+        let discovered = self::registry([list]).discover().unwrap();
+
+        for registry in [undiscovered, discovered] {
+            let list = Dereferencer::new(&registry)
+                .max_depth(1)
+                .dereference("https://example.com/list")
+                .unwrap();
+
+            assert_eq!(to_value(list)["properties"]["head"], head);
+        }
+    }
+
+    #[test]
+    fn recursive_reference_dereference_example() {
+        let registry = registry([
+            json!({
+                "$schema": "https://json-schema.org/draft/2019-09/schema",
+                "$id": "https://example.com/tree",
+                "$recursiveAnchor": true,
+                "type": "object",
+                "properties": {
+                    "children": { "type": "array", "items": { "$recursiveRef": "#" } }
+                }
+            }),
+            json!({
+                "$schema": "https://json-schema.org/draft/2019-09/schema",
+                "$id": "https://example.com/strict-tree",
+                "$recursiveAnchor": true,
+                "$ref": "tree",
+                "unevaluatedProperties": false
+            }),
+        ]);
+
+        let strict_tree = Dereferencer::new(&registry)
+            .max_depth(1)
+            .dereference("https://example.com/strict-tree")
+            .unwrap();
+        let children = json!({
+            "type": "array",
+            "items": { "$recursiveRef": "https://example.com/tree" }
+        });
+
+        assert_eq!(
+            to_value(strict_tree),
+            json!({
+                "$schema": "https://json-schema.org/draft/2019-09/schema",
+                "$id": "https://example.com/strict-tree",
+                "$recursiveAnchor": true,
+                "allOf": [{
+                    "type": "object",
+                    "properties": {
+                        "children": {
+                            "type": "array",
+                            "items": {
+                                "allOf": [{
+                                    "type": "object",
+                                    "properties": { "children": children }
+                                }],
+                                "unevaluatedProperties": false
+                            }
+                        }
+                    }
+                }],
+                "unevaluatedProperties": false
+            })
+        );
+    }
+}
@@ -0,0 +1,535 @@
+use std::collections::{BTreeSet, HashMap};
+use std::error::Error;
+use std::fmt::{Display, Write};
+
+use schema_discovery::{resolve_uri, SchemaDiscoverable};
+use serde_json_schema::{walk_schema, JsonPointer, Schema, Visitor};
+
+use crate::{rebase, strip_empty_fragment, SchemaRegistry};
+
+/// Which schemas in a registry depend on which, through references and nesting.
+#[derive(Debug, Clone)]
+pub struct DependencyGraph {
+    nodes: Vec<String>,
+    edges: Vec<Dependency>,
+}
+
+/// An edge of a [`DependencyGraph`], from the schema depending on another to that other schema.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Dependency {
+    from: String,
+    to: String,
+    kind: DependencyKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DependencyKind {
+    /// The schema is nested in the one depending on it.
+    Subschema,
+    /// The schema is what a `$ref`, `$dynamicRef` or `$recursiveRef`, as written here, resolves
+    /// to.
+    Reference(String),
+}
+
+impl Dependency {
+    pub fn from(&self) -> &str {
+        &self.from
+    }
+
+    pub fn to(&self) -> &str {
+        &self.to
+    }
+
+    pub fn kind(&self) -> &DependencyKind {
+        &self.kind
+    }
+}
+
+impl SchemaRegistry {
+    /// The graph with a node for every registered schema and every subschema discovered in them,
+    /// by the same ids [`SchemaRegistry::discover`] uses. An `$anchor` is an alias of its schema,
+    /// not a node of its own. References that don't resolve to a node have no edge.
+    pub fn dependency_graph(&self) -> DependencyGraph {
+        let mut documents = self.schemas.iter().collect::<Vec<(&String, &Schema)>>();
+        documents.sort_by_key(|(id, _)| *id);
+
+        let mut nodes = BTreeSet::new();
+        let mut ids = HashMap::new();
+        let mut aliases = HashMap::new();
+
+        for (id, document) in &documents {
+            nodes.insert(id.to_string());
+            ids.insert(*document as *const Schema, id.to_string());
+
+            for discovered in document.discover() {
+                nodes.insert(discovered.id().to_owned());
+                ids.entry(discovered.schema() as *const Schema)
+                    .or_insert_with(|| discovered.id().to_owned());
+
+                if let Some(anchor) = discovered.anchor_id() {
+                    aliases.insert(anchor, discovered.id().to_owned());
+                }
+            }
+        }
+
+        let mut collector = DependencyCollector {
+            registry: self,
+            nodes: &nodes,
+            ids: &ids,
+            aliases: &aliases,
+            scopes: Vec::new(),
+            edges: Vec::new(),
+        };
+
+        for (id, document) in documents {
+            collector.scopes.push((id.clone(), id.clone()));
+            collector.visit_schema(&JsonPointer::root(), document);
+            collector.scopes.pop();
+        }
+
+        DependencyGraph {
+            edges: collector.edges,
+            nodes: nodes.into_iter().collect(),
+        }
+    }
+}
+
+impl DependencyGraph {
+    /// The ids of the schemas, sorted.
+    pub fn nodes(&self) -> &[String] {
+        &self.nodes
+    }
+
+    pub fn edges(&self) -> &[Dependency] {
+        &self.edges
+    }
+
+    /// The schemas the one with the id depends on directly.
+    pub fn dependencies<'g>(&'g self, id: &'g str) -> impl Iterator<Item = &'g str> {
+        self.edges
+            .iter()
+            .filter(move |edge| edge.from == id)
+            .map(|edge| edge.to.as_str())
+    }
+
+    /// The schemas depending directly on the one with the id, i.e. the ones a change to it
+    /// affects.
+    pub fn dependents<'g>(&'g self, id: &'g str) -> impl Iterator<Item = &'g str> {
+        self.edges
+            .iter()
+            .filter(move |edge| edge.to == id)
+            .map(|edge| edge.from.as_str())
+    }
+
+    /// The groups of schemas that depend on each other, with every schema in exactly one group.
+    /// Groups come after the ones they depend on, and their members are sorted.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<&str>> {
+        let index = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.as_str(), index))
+            .collect::<HashMap<&str, usize>>();
+
+        let mut successors = vec![BTreeSet::new(); self.nodes.len()];
+        for edge in &self.edges {
+            successors[index[edge.from.as_str()]].insert(index[edge.to.as_str()]);
+        }
+
+        let mut tarjan = Tarjan {
+            successors: &successors,
+            index: vec![None; self.nodes.len()],
+            low_link: vec![0; self.nodes.len()],
+            on_stack: vec![false; self.nodes.len()],
+            stack: Vec::new(),
+            next_index: 0,
+            components: Vec::new(),
+        };
+
+        for node in 0..self.nodes.len() {
+            if tarjan.index[node].is_none() {
+                tarjan.connect(node);
+            }
+        }
+
+        tarjan
+            .components
+            .into_iter()
+            .map(|mut component| {
+                component.sort();
+                component
+                    .into_iter()
+                    .map(|node| self.nodes[node].as_str())
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The groups of schemas that recursively depend on themselves, through one another or
+    /// directly.
+    pub fn recursive_groups(&self) -> Vec<Vec<&str>> {
+        self.strongly_connected_components()
+            .into_iter()
+            .filter(|component| match component[..] {
+                [node] => self.dependencies(node).any(|dependency| dependency == node),
+                _ => true,
+            })
+            .collect()
+    }
+
+    /// Every schema after the ones it depends on, e.g. in the order to generate them in.
+    pub fn topological_order(&self) -> Result<Vec<&str>, DependencyGraphError> {
+        let recursive = self.recursive_groups();
+
+        if !recursive.is_empty() {
+            return Err(DependencyGraphError::RecursiveGroups(
+                recursive
+                    .into_iter()
+                    .map(|group| group.into_iter().map(str::to_owned).collect())
+                    .collect(),
+            ));
+        }
+
+        Ok(self
+            .strongly_connected_components()
+            .into_iter()
+            .flatten()
+            .collect())
+    }
+
+    /// The graph in Graphviz's DOT language, with nesting drawn dashed.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph schemas {\n");
+
+        for node in &self.nodes {
+            let _ = writeln!(dot, "    {};", dot_id(node));
+        }
+
+        for edge in &self.edges {
+            let _ = match &edge.kind {
+                DependencyKind::Subschema => writeln!(
+                    dot,
+                    "    {} -> {} [style=dashed];",
+                    dot_id(&edge.from),
+                    dot_id(&edge.to)
+                ),
+                DependencyKind::Reference(reference) => writeln!(
+                    dot,
+                    "    {} -> {} [label={}];",
+                    dot_id(&edge.from),
+                    dot_id(&edge.to),
+                    dot_id(reference)
+                ),
+            };
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// The graph as a Mermaid flowchart, with nesting drawn dotted.
+    pub fn to_mermaid(&self) -> String {
+        let index = self
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(index, node)| (node.as_str(), index))
+            .collect::<HashMap<&str, usize>>();
+        let mut mermaid = String::from("flowchart LR\n");
+
+        for (index, node) in self.nodes.iter().enumerate() {
+            let _ = writeln!(mermaid, "    n{index}[\"{}\"]", mermaid_text(node));
+        }
+
+        for edge in &self.edges {
+            let from = index[edge.from.as_str()];
+            let to = index[edge.to.as_str()];
+            let _ = match &edge.kind {
+                DependencyKind::Subschema => writeln!(mermaid, "    n{from} -.-> n{to}"),
+                DependencyKind::Reference(reference) => writeln!(
+                    mermaid,
+                    "    n{from} -->|\"{}\"| n{to}",
+                    mermaid_text(reference)
+                ),
+            };
+        }
+
+        mermaid
+    }
+}
+
+/// Tarjan's algorithm, which finds each strongly connected component only after the ones it
+/// has edges to.
+struct Tarjan<'s> {
+    successors: &'s [BTreeSet<usize>],
+    index: Vec<Option<usize>>,
+    low_link: Vec<usize>,
+    on_stack: Vec<bool>,
+    stack: Vec<usize>,
+    next_index: usize,
+    components: Vec<Vec<usize>>,
+}
+
+impl Tarjan<'_> {
+    fn connect(&mut self, node: usize) {
+        self.index[node] = Some(self.next_index);
+        self.low_link[node] = self.next_index;
+        self.next_index += 1;
+        self.stack.push(node);
+        self.on_stack[node] = true;
+
+        for &successor in self.successors[node].iter() {
+            match self.index[successor] {
+                None => {
+                    self.connect(successor);
+                    self.low_link[node] = self.low_link[node].min(self.low_link[successor]);
+                }
+                Some(index) if self.on_stack[successor] => {
+                    self.low_link[node] = self.low_link[node].min(index);
+                }
+                Some(_) => {}
+            }
+        }
+
+        if Some(self.low_link[node]) == self.index[node] {
+            let mut component = Vec::new();
+
+            while let Some(member) = self.stack.pop() {
+                self.on_stack[member] = false;
+                component.push(member);
+
+                if member == node {
+                    break;
+                }
+            }
+
+            self.components.push(component);
+        }
+    }
+}
+
+/// Walks a registered document, attributing each reference to the closest node it is in.
+struct DependencyCollector<'c> {
+    registry: &'c SchemaRegistry,
+    nodes: &'c BTreeSet<String>,
+    ids: &'c HashMap<*const Schema, String>,
+    aliases: &'c HashMap<String, String>,
+    /// The closest node and the base URI of each schema on the way to the current one.
+    scopes: Vec<(String, String)>,
+    edges: Vec<Dependency>,
+}
+
+impl DependencyCollector<'_> {
+    fn target(&self, base_uri: &str, reference: &str) -> Option<String> {
+        let uri = strip_empty_fragment(resolve_uri(base_uri, reference));
+
+        if self.nodes.contains(&uri) {
+            return Some(uri);
+        }
+
+        if let Some(id) = self.aliases.get(&uri) {
+            return Some(id.clone());
+        }
+
+        let resolved = self.registry.resolve(base_uri, reference)?;
+        self.ids.get(&(resolved.schema() as *const Schema)).cloned()
+    }
+}
+
+impl<'a> Visitor<'a> for DependencyCollector<'_> {
+    fn visit_schema(&mut self, pointer: &JsonPointer, schema: &'a Schema) {
+        let (parent, parent_base) = self.scopes.last().cloned().unwrap_or_default();
+        let base_uri = rebase(&parent_base, schema);
+        let node = match self.ids.get(&(schema as *const Schema)) {
+            Some(id) if *id != parent => {
+                self.edges.push(Dependency {
+                    from: parent,
+                    to: id.clone(),
+                    kind: DependencyKind::Subschema,
+                });
+                id.clone()
+            }
+            _ => parent,
+        };
+
+        let references = [
+            &schema.reference,
+            &schema.dynamic_reference,
+            &schema.recursive_reference,
+        ];
+        for reference in references.into_iter().flatten() {
+            if let Some(target) = self.target(&base_uri, reference) {
+                self.edges.push(Dependency {
+                    from: node.clone(),
+                    to: target,
+                    kind: DependencyKind::Reference(reference.clone()),
+                });
+            }
+        }
+
+        self.scopes.push((node, base_uri));
+        walk_schema(self, pointer, schema);
+        self.scopes.pop();
+    }
+}
+
+fn dot_id(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn mermaid_text(text: &str) -> String {
+    text.replace('"', "#quot;")
+}
+
+#[derive(Debug)]
+pub enum DependencyGraphError {
+    RecursiveGroups(Vec<Vec<String>>),
+}
+
+impl Error for DependencyGraphError {}
+
+impl Display for DependencyGraphError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RecursiveGroups(groups) => {
+                let groups = groups
+                    .iter()
+                    .map(|group| format!("[{}]", group.join(", ")))
+                    .collect::<Vec<String>>();
+
+                write!(
+                    f,
+                    "The schemas can't be ordered, as these depend on themselves: {}",
+                    groups.join(", ")
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn registry(schemas: impl IntoIterator<Item = serde_json::Value>) -> SchemaRegistry {
+        schemas
+            .into_iter()
+            .fold(SchemaRegistry::new(), |registry, schema| {
+                let schema = serde_json::from_value(schema).unwrap();
+                registry.add_internally_identified_schema(schema).unwrap()
+            })
+            .discover()
+            .unwrap()
+    }
+
+    #[test]
+    fn dependency_graph_example() {
+        let registry = registry([
+            json!({
+                "$id": "https://example.com/order",
+                "properties": {
+                    "customer": { "$ref": "customer" },
+                    "product": { "$ref": "product#sku" }
+                }
+            }),
+            json!({
+                "$id": "https://example.com/customer",
+                "properties": { "name": { "type": "string" } }
+            }),
+            json!({
+                "$id": "https://example.com/product",
+                "$defs": { "sku": { "$anchor": "sku", "type": "string" } }
+            }),
+        ]);
+        let graph = registry.dependency_graph();
+
+        assert_eq!(
+            graph.nodes(),
+            [
+                "https://example.com/customer",
+                "https://example.com/customer#/properties/name",
+                "https://example.com/order",
+                "https://example.com/order#/properties/customer",
+                "https://example.com/order#/properties/product",
+                "https://example.com/product",
+                "https://example.com/product#/$defs/sku",
+            ]
+        );
+        assert_eq!(
+            graph
+                .dependencies("https://example.com/order#/properties/product")
+                .collect::<Vec<&str>>(),
+            ["https://example.com/product#/$defs/sku"]
+        );
+        assert_eq!(
+            graph
+                .dependents("https://example.com/customer")
+                .collect::<Vec<&str>>(),
+            ["https://example.com/order#/properties/customer"]
+        );
+
+        let order = graph.topological_order().unwrap();
+        let position = |id| order.iter().position(|node| *node == id).unwrap();
+
+        assert_eq!(order.len(), graph.nodes().len());
+        assert!(position("https://example.com/customer") < position("https://example.com/order"));
+        assert!(
+            position("https://example.com/product#/$defs/sku")
+                < position("https://example.com/order")
+        );
+
+        assert!(graph.to_dot().contains(
+            "    \"https://example.com/order#/properties/customer\" -> \"https://example.com/customer\" [label=\"customer\"];\n"
+        ));
+        assert!(graph.to_mermaid().contains("    n3 -->|\"customer\"| n0\n"));
+        assert!(graph.to_mermaid().contains("    n2 -.-> n3\n"));
+    }
+
+    #[test]
+    fn recursive_groups_example() {
+        let registry = registry([
+            json!({
+                "$id": "https://example.com/person",
+                "properties": { "employer": { "$ref": "company" } }
+            }),
+            json!({
+                "$id": "https://example.com/company",
+                "properties": { "employees": { "items": { "$ref": "person" } } }
+            }),
+            json!({
+                "$id": "https://example.com/tree",
+                "properties": { "children": { "items": { "$ref": "#" } } }
+            }),
+            json!({
+                "$id": "https://example.com/legacy-tree",
+                "$recursiveAnchor": true,
+                "properties": { "children": { "items": { "$recursiveRef": "#" } } }
+            }),
+            json!({ "$id": "https://example.com/leaf", "type": "string" }),
+        ]);
+        let graph = registry.dependency_graph();
+
+        let groups = graph.recursive_groups();
+        let roots = groups.iter().map(|group| group[0]).collect::<Vec<&str>>();
+
+        assert_eq!(
+            roots,
+            [
+                "https://example.com/company",
+                "https://example.com/legacy-tree",
+                "https://example.com/tree"
+            ]
+        );
+        assert!(groups[0].contains(&"https://example.com/person#/properties/employer"));
+        assert!(graph
+            .strongly_connected_components()
+            .contains(&vec!["https://example.com/leaf"]));
+
+        match graph.topological_order() {
+            Err(DependencyGraphError::RecursiveGroups(groups)) => assert_eq!(groups.len(), 3),
+            other => panic!("{other:?}"),
+        }
+    }
+}
@@ -1,12 +1,40 @@
 use std::{collections::HashMap, error::Error, fmt::Display};
 
-use schema_discovery::SchemaDiscoverable;
-use serde_json_schema::Schema;
+use elsa::sync::FrozenMap;
+
+use schema_discovery::{resolve_uri, SchemaDiscoverable};
+use serde_json_schema::{JsonPointer, Schema};
+
+mod bundle;
+mod check;
+mod dereference;
+mod graph;
+mod loader;
+#[cfg(feature = "standard_metaschemas")]
+mod metaschemas;
+mod retrieve;
+mod validate;
+
+pub use bundle::{Bundle, BundleError};
+pub use check::{Diagnostic, DiagnosticKind, Location};
+pub use dereference::{DereferenceError, Dereferencer};
+pub use graph::{Dependency, DependencyGraph, DependencyGraphError, DependencyKind};
+pub use loader::{file_uri, SchemaLoadError, SchemaLoader};
+pub use retrieve::{FileSystemRetriever, InMemoryRetriever, SchemaRetrievalError, SchemaRetriever};
+pub use validate::{IngestionValidator, SchemaViolation};
 
 #[derive(Default)]
 pub struct SchemaRegistry {
     discovered_schemas: HashMap<String, Schema>,
     schemas: HashMap<String, Schema>,
+    /// The ids discovered in each registered document that has been discovered, so that they go
+    /// with it when it is replaced or removed.
+    discovered_ids: HashMap<String, Vec<String>>,
+    retriever: Option<Box<dyn SchemaRetriever>>,
+    /// Documents fetched by the retriever and the schemas discovered in them, so that nothing is
+    /// fetched twice. Failures aren't kept, so that they can be retried.
+    retrieved: FrozenMap<String, Box<Schema>>,
+    ingestion_validator: Option<Box<dyn IngestionValidator>>,
 }
 
 impl SchemaRegistry {
@@ -18,17 +46,9 @@ impl SchemaRegistry {
         mut self,
         schema: Schema,
     ) -> Result<Self, SchemaRegistryIngestionError> {
-        let id = schema
-            .dollar_id
-            .clone()
-            .or(schema.id.clone())
-            .ok_or(SchemaRegistryIngestionError::NoInternalIdentifier)?;
-
-        if self.schema_exists(&id) {
-            return Err(SchemaRegistryIngestionError::SchemaAlreadyExistsInRegistry);
-        }
+        let id = internal_id(&schema)?;
 
-        self.schemas.insert(id, schema);
+        self.insert(id, schema)?;
         Ok(self)
     }
 
@@ -37,41 +57,237 @@ impl SchemaRegistry {
         external_id: String,
         schema: Schema,
     ) -> Result<Self, SchemaRegistryIngestionError> {
-        if self.schema_exists(&external_id) {
-            return Err(SchemaRegistryIngestionError::SchemaAlreadyExistsInRegistry);
+        self.insert(external_id, schema)?;
+        Ok(self)
+    }
+
+    /// Discovers the identifiable subschemas and anchors in the registered documents that
+    /// haven't been discovered yet.
+    pub fn discover(mut self) -> Result<Self, SchemaRegistryDiscoveryError> {
+        let mut undiscovered = self
+            .schemas
+            .keys()
+            .filter(|id| !self.discovered_ids.contains_key(*id))
+            .cloned()
+            .collect::<Vec<String>>();
+        undiscovered.sort();
+
+        for id in undiscovered {
+            self.discover_document(&id)
+                .map_err(SchemaRegistryDiscoveryError::EncounteredDuplicateSchema)?;
         }
 
-        self.schemas.insert(external_id, schema);
         Ok(self)
     }
 
-    pub fn discover(mut self) -> Result<Self, SchemaRegistryDiscoveryError> {
-        let iter = self.schemas.values().flat_map(|schema| {
-            schema.discover().map(|d| {
-                (
-                    d.id().to_owned(),
-                    d.anchor_id().map(|s| s.to_owned()),
-                    d.schema().clone(),
-                )
-            })
-        });
+    /// Registers a schema under its own id and discovers the schemas in it right away, leaving
+    /// the registry as it was if any of their ids is taken.
+    pub fn insert_internally_identified_schema(
+        &mut self,
+        schema: Schema,
+    ) -> Result<(), SchemaRegistryIngestionError> {
+        let id = internal_id(&schema)?;
 
-        for (id, anchor, schema) in iter {
-            if self.schema_exists(&id) {
-                return Err(SchemaRegistryDiscoveryError::EncounteredDuplicateSchema);
-            }
+        self.insert_externally_referenced_schema(id, schema)
+    }
+
+    /// Registers a schema under the id and discovers the schemas in it right away, leaving the
+    /// registry as it was if any of their ids is taken.
+    pub fn insert_externally_referenced_schema(
+        &mut self,
+        external_id: String,
+        schema: Schema,
+    ) -> Result<(), SchemaRegistryIngestionError> {
+        self.insert(external_id.clone(), schema)?;
 
-            self.discovered_schemas.insert(id, schema.clone());
-            if let Some(anchor) = anchor {
-                self.discovered_schemas.insert(anchor.to_owned(), schema);
+        if let Err(duplicate) = self.discover_document(&external_id) {
+            self.schemas.remove(&external_id);
+            return Err(SchemaRegistryIngestionError::DiscoveredSchemaAlreadyExists(
+                duplicate,
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the schema registered under the id, along with the schemas discovered in it, and
+    /// returns the one it replaced. The registry is left as it was if the new schema can't be
+    /// registered.
+    pub fn replace_schema(
+        &mut self,
+        id: &str,
+        schema: Schema,
+    ) -> Result<Schema, SchemaRegistryIngestionError> {
+        let was_discovered = self.discovered_ids.contains_key(id);
+        let previous = self
+            .remove_schema(id)
+            .ok_or(SchemaRegistryIngestionError::NoSuchSchemaInRegistry)?;
+
+        match self.insert_externally_referenced_schema(id.to_owned(), schema) {
+            Ok(()) => Ok(previous),
+            Err(error) => {
+                self.schemas.insert(id.to_owned(), previous);
+                if was_discovered {
+                    self.discover_document(id)
+                        .expect("the schema was discovered before it was replaced");
+                }
+
+                Err(error)
             }
         }
+    }
 
-        Ok(self)
+    /// Removes the schema registered under the id, along with the schemas discovered in it. The
+    /// documents fetched by the retriever are dropped as well, as some of them may only have
+    /// been fetched for want of the schema, and are fetched again when references need them.
+    pub fn remove_schema(&mut self, id: &str) -> Option<Schema> {
+        let schema = self.schemas.remove(id)?;
+
+        for discovered in self.discovered_ids.remove(id).unwrap_or_default() {
+            self.discovered_schemas.remove(&discovered);
+        }
+        self.retrieved = FrozenMap::new();
+
+        Some(schema)
+    }
+
+    /// The ids of the schemas and anchors discovered in the document registered under the id.
+    pub fn discovered_ids(&self, id: &str) -> &[String] {
+        self.discovered_ids.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    /// Fetches the documents references point at that aren't registered with the retriever,
+    /// when they are first resolved.
+    pub fn retriever(mut self, retriever: impl SchemaRetriever + 'static) -> Self {
+        self.retriever = Some(Box::new(retriever));
+        self
+    }
+
+    /// Validates every schema registered from here on, refusing the ones with violations with
+    /// [`SchemaRegistryIngestionError::InvalidSchema`].
+    pub fn validate_on_ingestion(mut self, validator: impl IngestionValidator + 'static) -> Self {
+        self.ingestion_validator = Some(Box::new(validator));
+        self
     }
 
     pub fn get(&self, id: &str) -> Option<&Schema> {
-        self.schemas.get(id).or(self.discovered_schemas.get(id))
+        self.schemas
+            .get(id)
+            .or(self.discovered_schemas.get(id))
+            .or_else(|| self.retrieved.get(id))
+    }
+
+    /// Resolves a reference the way `$ref` does: against the base URI of the schema it appears
+    /// in, then into the registered document by anchor or JSON pointer fragment.
+    pub fn resolve(&self, base_uri: &str, reference: &str) -> Option<ResolvedSchema<'_>> {
+        self.try_resolve(base_uri, reference).ok().flatten()
+    }
+
+    /// Resolves a reference like [`SchemaRegistry::resolve`], but tells why the retriever could
+    /// not fetch the document it points at.
+    pub fn try_resolve(
+        &self,
+        base_uri: &str,
+        reference: &str,
+    ) -> Result<Option<ResolvedSchema<'_>>, SchemaRetrievalError> {
+        let uri = strip_empty_fragment(resolve_uri(base_uri, reference));
+        let (document, fragment) = uri.split_once('#').unwrap_or((&uri, ""));
+
+        if let Some(schema) = self.get(&uri) {
+            return Ok(Some(ResolvedSchema::new(schema, rebase(document, schema))));
+        }
+
+        let root = match self.get(document) {
+            Some(root) => root,
+            None => match self.retrieve(document)? {
+                Some(root) => root,
+                None => return Ok(None),
+            },
+        };
+
+        Ok(ResolvedSchema::new(root, rebase(document, root)).fragment(fragment))
+    }
+
+    /// Resolves a reference the way `$dynamicRef` does, which is like `$ref` unless it lands on
+    /// a `$dynamicAnchor`, in which case the dynamic scope decides. See [`DynamicScope::resolve`].
+    pub fn resolve_dynamic<'a>(
+        &'a self,
+        base_uri: &str,
+        reference: &str,
+        scope: &DynamicScope<'a>,
+    ) -> Option<ResolvedSchema<'a>> {
+        self.resolve(base_uri, reference)
+            .map(|initial| scope.resolve(reference, initial))
+    }
+
+    fn retrieve(&self, uri: &str) -> Result<Option<&Schema>, SchemaRetrievalError> {
+        let Some(retriever) = &self.retriever else {
+            return Ok(None);
+        };
+
+        if let Some(document) = self.retrieved.get(uri) {
+            return Ok(Some(document));
+        }
+
+        let document = self
+            .retrieved
+            .insert(uri.to_owned(), Box::new(retriever.retrieve(uri)?));
+
+        for discovered in document.discover() {
+            let schema = discovered.schema();
+
+            if let Some(anchor) = discovered.anchor_id() {
+                self.retrieved.insert(anchor, Box::new(schema.clone()));
+            }
+            self.retrieved
+                .insert(discovered.id().to_owned(), Box::new(schema.clone()));
+        }
+
+        Ok(Some(document))
+    }
+
+    fn insert(&mut self, id: String, schema: Schema) -> Result<(), SchemaRegistryIngestionError> {
+        if self.schema_exists(&id) {
+            return Err(SchemaRegistryIngestionError::SchemaAlreadyExistsInRegistry);
+        }
+
+        if let Some(validator) = &self.ingestion_validator {
+            validator
+                .validate(&schema)
+                .map_err(SchemaRegistryIngestionError::InvalidSchema)?;
+        }
+
+        self.schemas.insert(id, schema);
+        Ok(())
+    }
+
+    /// Discovers the schemas in a registered document, registering none of them if any of their
+    /// ids is taken, which is the id returned then.
+    fn discover_document(&mut self, id: &str) -> Result<(), String> {
+        let mut discovered: Vec<(String, Schema)> = Vec::new();
+
+        for d in self.schemas[id].discover() {
+            discovered.push((d.id().to_owned(), d.schema().clone()));
+            if let Some(anchor) = d.anchor_id() {
+                discovered.push((anchor, d.schema().clone()));
+            }
+        }
+
+        for (index, (discovered_id, _)) in discovered.iter().enumerate() {
+            if self.schema_exists(discovered_id)
+                || discovered[..index]
+                    .iter()
+                    .any(|(other, _)| other == discovered_id)
+            {
+                return Err(discovered_id.clone());
+            }
+        }
+
+        let ids = discovered.iter().map(|(id, _)| id.clone()).collect();
+        self.discovered_schemas.extend(discovered);
+        self.discovered_ids.insert(id.to_owned(), ids);
+
+        Ok(())
     }
 
     fn schema_exists(&self, id: &str) -> bool {
@@ -79,10 +295,198 @@ impl SchemaRegistry {
     }
 }
 
+/// A schema a reference resolved to, with the base URI references inside it resolve against.
+#[derive(Debug, Clone)]
+pub struct ResolvedSchema<'a> {
+    schema: &'a Schema,
+    base_uri: String,
+}
+
+impl<'a> ResolvedSchema<'a> {
+    pub fn new(schema: &'a Schema, base_uri: String) -> Self {
+        ResolvedSchema { schema, base_uri }
+    }
+
+    pub fn schema(&self) -> &'a Schema {
+        self.schema
+    }
+
+    pub fn base_uri(&self) -> &str {
+        &self.base_uri
+    }
+
+    /// The schema a URI fragment identifies within this one, which is either a JSON pointer or
+    /// the plain name of an anchor in the same schema resource. The empty fragment is the schema
+    /// itself.
+    pub fn fragment(&self, fragment: &str) -> Option<ResolvedSchema<'a>> {
+        if fragment.is_empty() {
+            return Some(self.clone());
+        }
+
+        if !fragment.starts_with('/') {
+            return find_anchor(self.schema, fragment)
+                .map(|schema| ResolvedSchema::new(schema, self.base_uri.clone()));
+        }
+
+        let pointer = JsonPointer::from_fragment(fragment).ok()?;
+        let tokens = pointer.tokens().collect::<Vec<&str>>();
+
+        // Every schema passed on the way may change the base URI with its own id.
+        let base_uri = (1..tokens.len())
+            .filter_map(|length| {
+                self.schema
+                    .pointer(&tokens[..length].iter().copied().collect())
+            })
+            .fold(self.base_uri.clone(), |base_uri, schema| {
+                rebase(&base_uri, schema)
+            });
+        let schema = self.schema.pointer(&pointer)?;
+
+        Some(ResolvedSchema::new(schema, rebase(&base_uri, schema)))
+    }
+}
+
+/// The schema resources evaluation has entered on its way to the current schema, outermost
+/// first. This is what a `$dynamicRef` is resolved against.
+#[derive(Debug, Clone, Default)]
+pub struct DynamicScope<'a> {
+    resources: Vec<ResolvedSchema<'a>>,
+}
+
+impl<'a> DynamicScope<'a> {
+    pub fn new() -> Self {
+        DynamicScope::default()
+    }
+
+    /// Enters a schema resource, given the schema with the `$id` and the base URI it sets.
+    pub fn enter(&mut self, resource: &'a Schema, base_uri: String) {
+        self.resources.push(ResolvedSchema::new(resource, base_uri));
+    }
+
+    pub fn leave(&mut self) {
+        self.resources.pop();
+    }
+
+    pub fn resources(&self) -> &[ResolvedSchema<'a>] {
+        &self.resources
+    }
+
+    /// The schema a `$dynamicRef` resolves to, given the schema its reference resolves to
+    /// statically. Only when that schema declares a `$dynamicAnchor` named like the reference's
+    /// fragment is it replaced, by the outermost resource in scope declaring the same one.
+    pub fn resolve(&self, reference: &str, initial: ResolvedSchema<'a>) -> ResolvedSchema<'a> {
+        let name = reference
+            .split_once('#')
+            .map(|(_, fragment)| fragment)
+            .filter(|fragment| !fragment.is_empty() && !fragment.starts_with('/'));
+
+        let Some(name) = name.filter(|name| initial.schema.dynamic_anchor.as_deref() == Some(name))
+        else {
+            return initial;
+        };
+
+        self.resources
+            .iter()
+            .find_map(|resource| {
+                dynamic_anchors(resource.schema)
+                    .into_iter()
+                    .find(|(anchor, _)| *anchor == name)
+                    .map(|(_, schema)| ResolvedSchema::new(schema, resource.base_uri.clone()))
+            })
+            .unwrap_or(initial)
+    }
+
+    /// The schema a draft 2019-09 `$recursiveRef` resolves to, given the schema its reference
+    /// resolves to statically. Only when that schema sets `$recursiveAnchor` is it replaced, by
+    /// the outermost of the resources setting it too that were entered right before it.
+    pub fn resolve_recursive(&self, initial: ResolvedSchema<'a>) -> ResolvedSchema<'a> {
+        if initial.schema.recursive_anchor != Some(true) {
+            return initial;
+        }
+
+        self.resources
+            .iter()
+            .rev()
+            .take_while(|resource| resource.schema.recursive_anchor == Some(true))
+            .last()
+            .cloned()
+            .unwrap_or(initial)
+    }
+}
+
+/// Every schema in the resource rooted at `resource` that declares a `$dynamicAnchor`, along
+/// with its name. Nested resources with an id of their own are left out.
+pub fn dynamic_anchors(resource: &Schema) -> Vec<(&str, &Schema)> {
+    resource_schemas(resource)
+        .into_iter()
+        .filter_map(|schema| Some((schema.dynamic_anchor.as_deref()?, schema)))
+        .collect()
+}
+
+fn internal_id(schema: &Schema) -> Result<String, SchemaRegistryIngestionError> {
+    let id = schema
+        .get_id()
+        .ok_or(SchemaRegistryIngestionError::NoInternalIdentifier)?;
+
+    // An empty fragment identifies the same schema as none, as in discovery.
+    Ok(strip_empty_fragment(id))
+}
+
+fn strip_empty_fragment(uri: String) -> String {
+    match uri.strip_suffix('#') {
+        Some(uri) => uri.to_owned(),
+        None => uri,
+    }
+}
+
+/// The base URI of a schema, given the base URI of the schema it is nested in.
+fn rebase(base_uri: &str, schema: &Schema) -> String {
+    match schema.get_id().filter(|id| !id.starts_with('#')) {
+        Some(id) => strip_empty_fragment(resolve_uri(base_uri, &id)),
+        None => base_uri.to_owned(),
+    }
+}
+
+/// The schema with a plain name fragment within the same schema resource, which is either an
+/// `$anchor`, a `$dynamicAnchor` or an older `$id` consisting of just the fragment.
+fn find_anchor<'a>(resource: &'a Schema, name: &str) -> Option<&'a Schema> {
+    resource_schemas(resource).into_iter().find(|schema| {
+        schema.anchor.as_deref() == Some(name)
+            || schema.dynamic_anchor.as_deref() == Some(name)
+            || schema
+                .get_id()
+                .is_some_and(|id| id.strip_prefix('#') == Some(name))
+    })
+}
+
+/// The schemas making up a schema resource, starting with its root.
+fn resource_schemas(resource: &Schema) -> Vec<&Schema> {
+    let mut schemas = vec![resource];
+    let mut index = 0;
+
+    while let Some(schema) = schemas.get(index) {
+        let nested = schema
+            .subschemas()
+            .into_iter()
+            .filter_map(|(_, subschema)| subschema.as_schema())
+            // A subschema with its own id is a separate resource, with its own anchors.
+            .filter(|subschema| subschema.get_id().is_none_or(|id| id.starts_with('#')));
+
+        schemas.extend(nested);
+        index += 1;
+    }
+
+    schemas
+}
+
 #[derive(Debug)]
 pub enum SchemaRegistryIngestionError {
     NoInternalIdentifier,
     SchemaAlreadyExistsInRegistry,
+    NoSuchSchemaInRegistry,
+    /// The id of a schema discovered in the one being registered is already taken.
+    DiscoveredSchemaAlreadyExists(String),
+    InvalidSchema(Vec<SchemaViolation>),
 }
 
 impl Error for SchemaRegistryIngestionError {}
@@ -99,13 +503,36 @@ impl Display for SchemaRegistryIngestionError {
                     "Attempted to register a schema, but there is an existing schema with that id."
                 )
             }
+            Self::NoSuchSchemaInRegistry => {
+                write!(
+                    f,
+                    "Attempted to replace a schema, but there is no schema with that id."
+                )
+            }
+            Self::DiscoveredSchemaAlreadyExists(id) => {
+                write!(
+                    f,
+                    "Attempted to register a schema, but the schema discovered in it with the id {id} already exists."
+                )
+            }
+            Self::InvalidSchema(violations) => {
+                write!(f, "Attempted to register an invalid schema: ")?;
+                for (index, violation) in violations.iter().enumerate() {
+                    match index {
+                        0 => write!(f, "{violation}")?,
+                        _ => write!(f, "; {violation}")?,
+                    }
+                }
+                Ok(())
+            }
         }
     }
 }
 
 #[derive(Debug)]
 pub enum SchemaRegistryDiscoveryError {
-    EncounteredDuplicateSchema,
+    /// A discovered schema or anchor has an id that is already taken.
+    EncounteredDuplicateSchema(String),
 }
 
 impl Error for SchemaRegistryDiscoveryError {}
@@ -113,9 +540,271 @@ impl Error for SchemaRegistryDiscoveryError {}
 impl Display for SchemaRegistryDiscoveryError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            Self::EncounteredDuplicateSchema => {
-                write!(f, "Attempted to discover identifyable sub schemas from schemas in the repository, but while doing so encountered a duplicate.")
+            Self::EncounteredDuplicateSchema(id) => {
+                write!(f, "Attempted to discover identifyable sub schemas from schemas in the repository, but while doing so encountered a duplicate of {id}.")
             }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn registry() -> SchemaRegistry {
+        let schema: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/schemas/order",
+            "$defs": {
+                "product": {
+                    "$anchor": "ProductSchema",
+                    "title": "Product",
+                    "properties": { "name": { "type": "string" } }
+                },
+                "customer": {
+                    "$id": "customer",
+                    "title": "Customer",
+                    "$defs": { "address": { "$anchor": "address", "title": "Address" } }
+                }
+            }
+        }))
+        .unwrap();
+
+        SchemaRegistry::new()
+            .add_internally_identified_schema(schema)
+            .unwrap()
+            .discover()
+            .unwrap()
+    }
+
+    fn title(resolved: Option<ResolvedSchema>) -> Option<(String, String)> {
+        resolved.map(|resolved| {
+            (
+                resolved.schema().title.clone().unwrap_or_default(),
+                resolved.base_uri().to_owned(),
+            )
+        })
+    }
+
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-schema-references
+    #[test]
+    fn resolve_example() {
+        let registry = registry();
+        let base = "https://example.com/schemas/order";
+        let examples = [
+            ("#ProductSchema", Some(("Product", base))),
+            ("#/$defs/product", Some(("Product", base))),
+            ("order#/$defs/product", Some(("Product", base))),
+            ("#/$defs/product/properties/name", Some(("", base))),
+            (
+                "customer",
+                Some(("Customer", "https://example.com/schemas/customer")),
+            ),
+            (
+                "#/$defs/customer",
+                Some(("Customer", "https://example.com/schemas/customer")),
+            ),
+            (
+                "customer#address",
+                Some(("Address", "https://example.com/schemas/customer")),
+            ),
+            (
+                "#/$defs/customer/$defs/address",
+                Some(("Address", "https://example.com/schemas/customer")),
+            ),
+            ("#address", None),
+            ("#/$defs/missing", None),
+            ("https://example.com/schemas/missing", None),
+        ];
+
+        for (reference, expected) in examples {
+            assert_eq!(
+                title(registry.resolve(base, reference)),
+                expected.map(|(title, base)| (title.to_owned(), base.to_owned())),
+                "{reference}"
+            );
+        }
+    }
+
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-dynamic-references-with-dyn
+    #[test]
+    fn dynamic_reference_example() {
+        let tree: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/tree",
+            "$dynamicAnchor": "node",
+            "type": "object",
+            "properties": {
+                "data": true,
+                "children": { "type": "array", "items": { "$dynamicRef": "#node" } }
+            }
+        }))
+        .unwrap();
+        let strict_tree: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/strict-tree",
+            "$dynamicAnchor": "node",
+            "$ref": "tree",
+            "unevaluatedProperties": false
+        }))
+        .unwrap();
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(tree)
+            .unwrap()
+            .add_internally_identified_schema(strict_tree)
+            .unwrap();
+
+        let tree = registry.get("https://example.com/tree").unwrap();
+        let strict_tree = registry.get("https://example.com/strict-tree").unwrap();
+        let resolve = |scope: &DynamicScope, reference| {
+            registry
+                .resolve_dynamic("https://example.com/tree", reference, scope)
+                .map(|resolved| resolved.base_uri().to_owned())
+        };
+
+        let mut scope = DynamicScope::new();
+        scope.enter(tree, "https://example.com/tree".to_owned());
+
+        assert_eq!(
+            resolve(&scope, "#node"),
+            Some("https://example.com/tree".to_owned())
+        );
+
+        let mut scope = DynamicScope::new();
+        scope.enter(strict_tree, "https://example.com/strict-tree".to_owned());
+        scope.enter(tree, "https://example.com/tree".to_owned());
+
+        assert_eq!(
+            resolve(&scope, "#node"),
+            Some("https://example.com/strict-tree".to_owned())
+        );
+        // Without a dynamic anchor fragment it is an ordinary reference.
+        assert_eq!(
+            resolve(&scope, "tree"),
+            Some("https://example.com/tree".to_owned())
+        );
+        assert_eq!(resolve(&scope, "#leaf"), None);
+    }
+
+    #[test]
+    fn incremental_registry_example() {
+        let order = |customer: serde_json::Value| -> Schema {
+            serde_json::from_value(json!({
+                "$id": "https://example.com/schemas/order",
+                "$defs": { "customer": customer }
+            }))
+            .unwrap()
+        };
+        let customer: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/schemas/customer",
+            "title": "Customer"
+        }))
+        .unwrap();
+
+        let mut registry = SchemaRegistry::new();
+        registry
+            .insert_internally_identified_schema(order(json!({
+                "$id": "customer",
+                "$defs": { "address": { "$anchor": "address" } }
+            })))
+            .unwrap();
+
+        assert_eq!(
+            registry.discovered_ids("https://example.com/schemas/order"),
+            [
+                "https://example.com/schemas/customer",
+                "https://example.com/schemas/customer#/$defs/address",
+                "https://example.com/schemas/customer#address",
+            ]
+        );
+        assert!(matches!(
+            registry.insert_internally_identified_schema(customer.clone()),
+            Err(SchemaRegistryIngestionError::SchemaAlreadyExistsInRegistry)
+        ));
+
+        registry
+            .replace_schema(
+                "https://example.com/schemas/order",
+                order(json!({ "$anchor": "customer" })),
+            )
+            .unwrap();
+
+        assert!(registry
+            .get("https://example.com/schemas/customer#address")
+            .is_none());
+        assert!(registry
+            .get("https://example.com/schemas/order#customer")
+            .is_some());
+
+        registry
+            .insert_internally_identified_schema(customer.clone())
+            .unwrap();
+
+        // The replacement clashes with the customer schema, so the order schema is kept.
+        assert!(matches!(
+            registry.replace_schema(
+                "https://example.com/schemas/order",
+                order(json!({ "$id": "customer" }))
+            ),
+            Err(SchemaRegistryIngestionError::DiscoveredSchemaAlreadyExists(id))
+                if id == "https://example.com/schemas/customer"
+        ));
+        assert!(registry
+            .get("https://example.com/schemas/order#customer")
+            .is_some());
+
+        assert!(registry
+            .remove_schema("https://example.com/schemas/order")
+            .is_some());
+        assert!(registry
+            .get("https://example.com/schemas/order#customer")
+            .is_none());
+        assert!(matches!(
+            registry.replace_schema("https://example.com/schemas/order", customer),
+            Err(SchemaRegistryIngestionError::NoSuchSchemaInRegistry)
+        ));
+
+        // Discovering again only discovers the documents added since.
+        let registry = registry
+            .add_internally_identified_schema(order(json!({ "$anchor": "customer" })))
+            .unwrap()
+            .discover()
+            .unwrap()
+            .discover()
+            .unwrap();
+
+        assert!(registry
+            .get("https://example.com/schemas/order#customer")
+            .is_some());
+    }
+
+    #[test]
+    fn duplicate_discovery_example() {
+        let order: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/schemas/order",
+            "$defs": { "customer": { "$id": "customer" } }
+        }))
+        .unwrap();
+        let customer: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/schemas/customer"
+        }))
+        .unwrap();
+
+        let error = SchemaRegistry::new()
+            .add_internally_identified_schema(order)
+            .unwrap()
+            .add_internally_identified_schema(customer)
+            .unwrap()
+            .discover()
+            .err()
+            .unwrap();
+
+        assert!(matches!(
+            &error,
+            SchemaRegistryDiscoveryError::EncounteredDuplicateSchema(id)
+                if id == "https://example.com/schemas/customer"
+        ));
+        assert!(error
+            .to_string()
+            .ends_with("encountered a duplicate of https://example.com/schemas/customer."));
+    }
+}
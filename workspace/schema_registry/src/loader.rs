@@ -0,0 +1,298 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use glob::{MatchOptions, Pattern, PatternError};
+use serde_json_schema::{Dialect, DialectError, Schema};
+
+use crate::{internal_id, SchemaRegistry, SchemaRegistryIngestionError};
+
+const MATCH_OPTIONS: MatchOptions = MatchOptions {
+    case_sensitive: true,
+    require_literal_separator: true,
+    require_literal_leading_dot: false,
+};
+
+/// Finds the schema files below a directory, to be registered with [`SchemaRegistry::load`].
+pub struct SchemaLoader {
+    directory: PathBuf,
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+    default_dialect: Dialect,
+}
+
+impl SchemaLoader {
+    pub fn new(directory: impl Into<PathBuf>) -> Self {
+        SchemaLoader {
+            directory: directory.into(),
+            include: Vec::new(),
+            exclude: Vec::new(),
+            default_dialect: Dialect::Draft2020_12,
+        }
+    }
+
+    /// Only loads the files whose path relative to the directory matches one of the included
+    /// patterns. Without any, every `.json` file is loaded, which covers `.schema.json` too.
+    pub fn include(mut self, pattern: &str) -> Result<Self, SchemaLoadError> {
+        self.include.push(Pattern::new(pattern)?);
+        Ok(self)
+    }
+
+    /// Skips the files whose path relative to the directory matches the pattern.
+    pub fn exclude(mut self, pattern: &str) -> Result<Self, SchemaLoadError> {
+        self.exclude.push(Pattern::new(pattern)?);
+        Ok(self)
+    }
+
+    /// The dialect of files that don't declare one with `$schema`.
+    pub fn default_dialect(mut self, dialect: Dialect) -> Self {
+        self.default_dialect = dialect;
+        self
+    }
+
+    /// The files to load, in a stable order.
+    pub fn files(&self) -> Result<Vec<PathBuf>, SchemaLoadError> {
+        let mut files = Vec::new();
+        self.collect_files(&self.directory, &mut files)?;
+        files.sort();
+
+        Ok(files)
+    }
+
+    fn collect_files(
+        &self,
+        directory: &Path,
+        files: &mut Vec<PathBuf>,
+    ) -> Result<(), SchemaLoadError> {
+        let entries = fs::read_dir(directory)
+            .map_err(|error| SchemaLoadError::Io(directory.into(), error))?;
+
+        for entry in entries {
+            let entry = entry.map_err(|error| SchemaLoadError::Io(directory.into(), error))?;
+            let path = entry.path();
+            let file_type = entry
+                .file_type()
+                .map_err(|error| SchemaLoadError::Io(path.clone(), error))?;
+
+            if file_type.is_dir() {
+                self.collect_files(&path, files)?;
+            } else if self.is_included(&path) {
+                files.push(path);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_included(&self, path: &Path) -> bool {
+        let relative = path.strip_prefix(&self.directory).unwrap_or(path);
+        let matches = |pattern: &Pattern| pattern.matches_path_with(relative, MATCH_OPTIONS);
+
+        let included = match self.include.is_empty() {
+            true => relative
+                .extension()
+                .is_some_and(|extension| extension == "json"),
+            false => self.include.iter().any(matches),
+        };
+
+        included && !self.exclude.iter().any(matches)
+    }
+
+    fn load_file(&self, path: &Path) -> Result<(String, Schema), SchemaLoadError> {
+        let json =
+            fs::read_to_string(path).map_err(|error| SchemaLoadError::Io(path.into(), error))?;
+        let schema = Schema::from_str_with_dialect(&json, self.default_dialect)
+            .map_err(|error| SchemaLoadError::Parse(path.into(), error))?;
+
+        let id = match internal_id(&schema) {
+            Ok(id) => id,
+            Err(_) => file_uri(path).map_err(|error| SchemaLoadError::Io(path.into(), error))?,
+        };
+
+        Ok((id, schema))
+    }
+}
+
+impl SchemaRegistry {
+    /// Registers every file the loader finds, under its `$id` or else its `file://` URI. Files
+    /// that fail to load are skipped, and all of their errors reported at the end, while the
+    /// others stay registered.
+    pub fn load(&mut self, loader: &SchemaLoader) -> Result<(), Vec<SchemaLoadError>> {
+        let mut errors = Vec::new();
+
+        for path in loader.files().map_err(|error| vec![error])? {
+            let loaded = loader.load_file(&path).and_then(|(id, schema)| {
+                self.insert(id, schema)
+                    .map_err(|error| SchemaLoadError::Ingestion(path.clone(), error))
+            });
+
+            if let Err(error) = loaded {
+                errors.push(error);
+            }
+        }
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+}
+
+/// The `file://` URI of a file, from its absolute path.
+pub fn file_uri(path: &Path) -> io::Result<String> {
+    let path = fs::canonicalize(path)?;
+    let path = path.to_string_lossy().replace('\\', "/");
+    let path = match path.starts_with('/') {
+        true => path,
+        false => format!("/{path}"),
+    };
+
+    let encoded = path
+        .bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => (byte as char).to_string(),
+            b'-' | b'.' | b'_' | b'~' | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+'
+            | b',' | b';' | b'=' | b':' | b'@' | b'/' => (byte as char).to_string(),
+            byte => format!("%{byte:02X}"),
+        })
+        .collect::<String>();
+
+    Ok(format!("file://{encoded}"))
+}
+
+#[derive(Debug)]
+pub enum SchemaLoadError {
+    InvalidPattern(PatternError),
+    Io(PathBuf, io::Error),
+    Parse(PathBuf, DialectError),
+    Ingestion(PathBuf, SchemaRegistryIngestionError),
+}
+
+impl SchemaLoadError {
+    /// The file or directory the error is about, if any.
+    pub fn path(&self) -> Option<&Path> {
+        match self {
+            Self::InvalidPattern(_) => None,
+            Self::Io(path, _) | Self::Parse(path, _) | Self::Ingestion(path, _) => Some(path),
+        }
+    }
+
+    /// The line and column of a syntax or data error in the file.
+    pub fn position(&self) -> Option<(usize, usize)> {
+        match self {
+            Self::Parse(_, DialectError::Deserialization(error)) => {
+                Some((error.line(), error.column()))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<PatternError> for SchemaLoadError {
+    fn from(error: PatternError) -> Self {
+        Self::InvalidPattern(error)
+    }
+}
+
+impl Error for SchemaLoadError {}
+
+impl Display for SchemaLoadError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidPattern(error) => write!(f, "Invalid file pattern: {error}"),
+            Self::Io(path, error) => write!(f, "Could not read {}: {error}", path.display()),
+            Self::Parse(path, error) => match self.position() {
+                Some((line, column)) => write!(f, "{}:{line}:{column}: {error}", path.display()),
+                None => write!(f, "{}: {error}", path.display()),
+            },
+            Self::Ingestion(path, error) => {
+                write!(f, "Could not register {}: {error}", path.display())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixtures(directory: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR"))
+            .join("fixtures")
+            .join(directory)
+    }
+
+    #[test]
+    fn load_directory_example() {
+        let directory = fixtures("schemas");
+        let address = file_uri(&directory.join("common/address.json")).unwrap();
+
+        let mut registry = SchemaRegistry::new();
+        registry.load(&SchemaLoader::new(&directory)).unwrap();
+
+        assert!(registry
+            .get("https://example.com/order.schema.json")
+            .is_some());
+        assert!(registry
+            .get("https://example.com/customer.schema.json")
+            .is_some());
+        assert!(registry.get(&address).is_some());
+        assert!(address.starts_with("file:///"));
+        assert!(address.ends_with("/fixtures/schemas/common/address.json"));
+
+        let loader = SchemaLoader::new(&directory)
+            .include("**/*.schema.json")
+            .unwrap()
+            .exclude("customer.*")
+            .unwrap();
+        let mut registry = SchemaRegistry::new();
+        registry.load(&loader).unwrap();
+
+        assert!(registry
+            .get("https://example.com/order.schema.json")
+            .is_some());
+        assert!(registry
+            .get("https://example.com/customer.schema.json")
+            .is_none());
+        assert!(registry.get(&address).is_none());
+    }
+
+    #[test]
+    fn load_errors_example() {
+        let directory = fixtures("broken");
+
+        let mut registry = SchemaRegistry::new();
+        let errors = registry.load(&SchemaLoader::new(&directory)).unwrap_err();
+
+        assert_eq!(errors.len(), 3);
+        assert_eq!(
+            errors[0].path(),
+            Some(directory.join("duplicate.json").as_path())
+        );
+        assert!(matches!(
+            errors[0],
+            SchemaLoadError::Ingestion(
+                _,
+                SchemaRegistryIngestionError::SchemaAlreadyExistsInRegistry
+            )
+        ));
+        assert_eq!(
+            errors[1].path(),
+            Some(directory.join("invalid.json").as_path())
+        );
+        assert_eq!(errors[1].position(), Some((3, 5)));
+        assert!(errors[1].to_string().contains("invalid.json:3:5"));
+        assert_eq!(
+            errors[2].path(),
+            Some(directory.join("mistyped.json").as_path())
+        );
+        assert_eq!(errors[2].position(), Some((4, 32)));
+        assert!(registry
+            .get("https://example.com/product.schema.json")
+            .is_some());
+
+        assert!(SchemaLoader::new(&directory).include("a**").is_err());
+    }
+}
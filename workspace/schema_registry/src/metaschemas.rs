@@ -0,0 +1,100 @@
+use serde_json_schema::{Dialect, Schema};
+
+use crate::SchemaRegistry;
+
+/// The official meta-schemas, as published at the URIs in their `$id`.
+const STANDARD_METASCHEMAS: [&str; 18] = [
+    include_str!("../metaschemas/draft-04/schema.json"),
+    include_str!("../metaschemas/draft-06/schema.json"),
+    include_str!("../metaschemas/draft-07/schema.json"),
+    include_str!("../metaschemas/draft/2019-09/schema.json"),
+    include_str!("../metaschemas/draft/2019-09/meta/core.json"),
+    include_str!("../metaschemas/draft/2019-09/meta/applicator.json"),
+    include_str!("../metaschemas/draft/2019-09/meta/validation.json"),
+    include_str!("../metaschemas/draft/2019-09/meta/meta-data.json"),
+    include_str!("../metaschemas/draft/2019-09/meta/format.json"),
+    include_str!("../metaschemas/draft/2019-09/meta/content.json"),
+    include_str!("../metaschemas/draft/2020-12/schema.json"),
+    include_str!("../metaschemas/draft/2020-12/meta/core.json"),
+    include_str!("../metaschemas/draft/2020-12/meta/applicator.json"),
+    include_str!("../metaschemas/draft/2020-12/meta/unevaluated.json"),
+    include_str!("../metaschemas/draft/2020-12/meta/validation.json"),
+    include_str!("../metaschemas/draft/2020-12/meta/meta-data.json"),
+    include_str!("../metaschemas/draft/2020-12/meta/format-annotation.json"),
+    include_str!("../metaschemas/draft/2020-12/meta/content.json"),
+];
+
+impl SchemaRegistry {
+    /// A registry that already holds the meta-schemas of draft-04 through 2020-12, including the
+    /// vocabulary meta-schemas of 2019-09 and 2020-12, so `$schema` URIs resolve offline.
+    pub fn with_standard_metaschemas() -> Self {
+        STANDARD_METASCHEMAS
+            .iter()
+            .fold(SchemaRegistry::new(), |registry, json| {
+                let schema = Schema::from_str_with_dialect(json, Dialect::Draft2020_12)
+                    .expect("the standard meta-schemas are valid schemas");
+
+                registry
+                    .add_internally_identified_schema(schema)
+                    .expect("the standard meta-schemas have distinct ids")
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DynamicScope;
+
+    #[test]
+    fn standard_metaschemas_example() {
+        let registry = SchemaRegistry::with_standard_metaschemas()
+            .discover()
+            .unwrap();
+
+        for dialect in [
+            Dialect::Draft4,
+            Dialect::Draft6,
+            Dialect::Draft7,
+            Dialect::Draft2019_09,
+            Dialect::Draft2020_12,
+        ] {
+            let metaschema = registry.resolve("", dialect.uri()).unwrap();
+
+            assert_eq!(metaschema.schema().dialect(), Some(dialect));
+        }
+
+        let metaschema = registry.get(Dialect::Draft2020_12.uri()).unwrap();
+
+        for vocabulary in metaschema.vocabulary.as_ref().unwrap().keys() {
+            let meta = vocabulary.replace("/vocab/", "/meta/");
+
+            assert!(registry.get(&meta).is_some(), "{meta} is missing");
+        }
+
+        let string_array = registry
+            .resolve(
+                Dialect::Draft2019_09.uri(),
+                "meta/validation#/$defs/stringArray",
+            )
+            .unwrap();
+
+        assert_eq!(
+            string_array.base_uri(),
+            "https://json-schema.org/draft/2019-09/meta/validation"
+        );
+
+        let mut scope = DynamicScope::new();
+        scope.enter(metaschema, Dialect::Draft2020_12.uri().to_owned());
+
+        let items = registry
+            .resolve_dynamic(
+                "https://json-schema.org/draft/2020-12/meta/applicator",
+                "#meta",
+                &scope,
+            )
+            .unwrap();
+
+        assert_eq!(items.base_uri(), Dialect::Draft2020_12.uri());
+    }
+}
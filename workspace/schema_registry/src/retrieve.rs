@@ -0,0 +1,278 @@
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use serde_json_schema::{percent_decode, Dialect, DialectError, Schema};
+
+/// Fetches the documents references point at that haven't been registered. The registry asks
+/// for each document by its URI without a fragment, until it has been retrieved once.
+pub trait SchemaRetriever: Send + Sync {
+    fn retrieve(&self, uri: &str) -> Result<Schema, SchemaRetrievalError>;
+}
+
+/// Retrieves `file://` URIs, and other URIs from the directories their prefix is mapped to.
+pub struct FileSystemRetriever {
+    prefixes: Vec<(String, PathBuf)>,
+    default_dialect: Dialect,
+}
+
+impl FileSystemRetriever {
+    pub fn new() -> Self {
+        FileSystemRetriever {
+            prefixes: Vec::new(),
+            default_dialect: Dialect::Draft2020_12,
+        }
+    }
+
+    /// Retrieves the URIs starting with the prefix from the directory, so mapping
+    /// `https://schemas.acme.com/` to `./schemas/` reads `https://schemas.acme.com/order.json`
+    /// from `./schemas/order.json`. The longest matching prefix wins, and the rest of the URI is
+    /// percent-decoded but may not leave the directory with `..`.
+    pub fn map_prefix(mut self, prefix: impl Into<String>, directory: impl Into<PathBuf>) -> Self {
+        self.prefixes.push((prefix.into(), directory.into()));
+        self
+    }
+
+    /// The dialect of documents that don't declare one with `$schema`.
+    pub fn default_dialect(mut self, dialect: Dialect) -> Self {
+        self.default_dialect = dialect;
+        self
+    }
+
+    fn path(&self, uri: &str) -> Option<PathBuf> {
+        let mapped = self
+            .prefixes
+            .iter()
+            .filter_map(|(prefix, directory)| Some((prefix, directory, uri.strip_prefix(prefix)?)))
+            .max_by_key(|(prefix, _, _)| prefix.len());
+
+        let Some((_, directory, path)) = mapped else {
+            let path = uri.strip_prefix("file://")?;
+            return percent_decode(path).map(PathBuf::from);
+        };
+
+        // A mapped URI may not reach outside of its directory.
+        let path = percent_decode(path)?;
+        let escapes = Path::new(&path).components().any(|component| {
+            matches!(
+                component,
+                Component::ParentDir | Component::RootDir | Component::Prefix(_)
+            )
+        });
+
+        (!escapes).then(|| directory.join(path))
+    }
+}
+
+impl Default for FileSystemRetriever {
+    fn default() -> Self {
+        FileSystemRetriever::new()
+    }
+}
+
+impl SchemaRetriever for FileSystemRetriever {
+    fn retrieve(&self, uri: &str) -> Result<Schema, SchemaRetrievalError> {
+        let path = self
+            .path(uri)
+            .ok_or(SchemaRetrievalError::UnsupportedUri(uri.to_owned()))?;
+        let json = fs::read_to_string(&path).map_err(|error| match error.kind() {
+            io::ErrorKind::NotFound => SchemaRetrievalError::NotFound(uri.to_owned()),
+            _ => SchemaRetrievalError::Io(uri.to_owned(), error),
+        })?;
+
+        Schema::from_str_with_dialect(&json, self.default_dialect)
+            .map_err(|error| SchemaRetrievalError::Parse(uri.to_owned(), error))
+    }
+}
+
+/// Retrieves documents handed to it up front, e.g. ones embedded in the binary.
+#[derive(Default)]
+pub struct InMemoryRetriever {
+    documents: HashMap<String, Schema>,
+}
+
+impl InMemoryRetriever {
+    pub fn new() -> Self {
+        InMemoryRetriever::default()
+    }
+
+    pub fn add_document(mut self, uri: impl Into<String>, schema: Schema) -> Self {
+        self.documents.insert(uri.into(), schema);
+        self
+    }
+}
+
+impl SchemaRetriever for InMemoryRetriever {
+    fn retrieve(&self, uri: &str) -> Result<Schema, SchemaRetrievalError> {
+        self.documents
+            .get(uri)
+            .cloned()
+            .ok_or(SchemaRetrievalError::NotFound(uri.to_owned()))
+    }
+}
+
+#[derive(Debug)]
+pub enum SchemaRetrievalError {
+    UnsupportedUri(String),
+    NotFound(String),
+    Io(String, io::Error),
+    Parse(String, DialectError),
+}
+
+impl Error for SchemaRetrievalError {}
+
+impl Display for SchemaRetrievalError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedUri(uri) => write!(f, "No way to retrieve {uri} is configured."),
+            Self::NotFound(uri) => write!(f, "There is no schema at {uri}."),
+            Self::Io(uri, error) => write!(f, "Could not read {uri}: {error}"),
+            Self::Parse(uri, error) => write!(f, "Could not parse {uri}: {error}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use serde_json::json;
+
+    use super::*;
+    use crate::{file_uri, SchemaRegistry};
+
+    struct CountingRetriever<R> {
+        inner: R,
+        count: Arc<AtomicUsize>,
+    }
+
+    impl<R: SchemaRetriever> SchemaRetriever for CountingRetriever<R> {
+        fn retrieve(&self, uri: &str) -> Result<Schema, SchemaRetrievalError> {
+            self.count.fetch_add(1, Ordering::SeqCst);
+            self.inner.retrieve(uri)
+        }
+    }
+
+    #[test]
+    fn file_system_retriever_example() {
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("fixtures/schemas");
+        let retriever = FileSystemRetriever::new()
+            .map_prefix("https://schemas.acme.com/", &directory)
+            .map_prefix("https://schemas.acme.com/common/", directory.join("common"));
+
+        assert_eq!(
+            retriever
+                .retrieve("https://schemas.acme.com/customer.schema.json")
+                .unwrap()
+                .title
+                .as_deref(),
+            Some("Customer")
+        );
+        assert_eq!(
+            retriever
+                .retrieve("https://schemas.acme.com/common/address.json")
+                .unwrap()
+                .title
+                .as_deref(),
+            Some("Address")
+        );
+
+        let address = file_uri(&directory.join("common/address.json")).unwrap();
+
+        assert!(retriever.retrieve(&address).is_ok());
+        assert!(matches!(
+            retriever.retrieve("https://schemas.acme.com/missing.json"),
+            Err(SchemaRetrievalError::NotFound(_))
+        ));
+        assert!(matches!(
+            retriever.retrieve("https://example.com/order.schema.json"),
+            Err(SchemaRetrievalError::UnsupportedUri(_))
+        ));
+        assert!(retriever
+            .retrieve("https://schemas.acme.com/common/%61ddress.json")
+            .is_ok());
+        assert!(matches!(
+            retriever.retrieve("https://schemas.acme.com/common/../customer.schema.json"),
+            Err(SchemaRetrievalError::UnsupportedUri(_))
+        ));
+        assert!(matches!(
+            retriever.retrieve("https://schemas.acme.com/common/%2E%2E/customer.schema.json"),
+            Err(SchemaRetrievalError::UnsupportedUri(_))
+        ));
+    }
+
+    #[test]
+    fn lazy_retrieval_example() {
+        let order: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/order.schema.json",
+            "properties": {
+                "product": { "$ref": "product.schema.json#/$defs/sku" }
+            }
+        }))
+        .unwrap();
+        let product: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/product.schema.json",
+            "$defs": { "sku": { "title": "Sku", "type": "string" } }
+        }))
+        .unwrap();
+
+        let count = Arc::new(AtomicUsize::new(0));
+        let retriever = CountingRetriever {
+            inner: InMemoryRetriever::new()
+                .add_document("https://example.com/product.schema.json", product),
+            count: count.clone(),
+        };
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(order)
+            .unwrap()
+            .retriever(retriever);
+
+        for _ in 0..2 {
+            let sku = registry
+                .resolve(
+                    "https://example.com/order.schema.json",
+                    "product.schema.json#/$defs/sku",
+                )
+                .unwrap();
+
+            assert_eq!(sku.schema().title.as_deref(), Some("Sku"));
+            assert_eq!(sku.base_uri(), "https://example.com/product.schema.json");
+        }
+
+        assert!(registry
+            .resolve(
+                "https://example.com/order.schema.json",
+                "customer.schema.json"
+            )
+            .is_none());
+        assert!(matches!(
+            registry.try_resolve(
+                "https://example.com/order.schema.json",
+                "customer.schema.json"
+            ),
+            Err(SchemaRetrievalError::NotFound(_))
+        ));
+        // Failures are retried, as opposed to documents that were retrieved.
+        assert_eq!(count.load(Ordering::SeqCst), 3);
+
+        // Removing a schema drops what was retrieved, which may have been fetched for it.
+        let mut registry = registry;
+        let order = registry
+            .remove_schema("https://example.com/order.schema.json")
+            .unwrap();
+        registry.insert_internally_identified_schema(order).unwrap();
+
+        assert!(registry
+            .resolve(
+                "https://example.com/order.schema.json",
+                "product.schema.json#/$defs/sku"
+            )
+            .is_some());
+        assert_eq!(count.load(Ordering::SeqCst), 4);
+    }
+}
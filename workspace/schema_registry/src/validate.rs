@@ -0,0 +1,39 @@
+use std::fmt::Display;
+
+use serde_json_schema::{JsonPointer, Schema};
+
+/// Checks schemas as they are registered, e.g. against their meta-schema. See
+/// [`SchemaRegistry::validate_on_ingestion`](crate::SchemaRegistry::validate_on_ingestion).
+pub trait IngestionValidator: Send + Sync {
+    fn validate(&self, schema: &Schema) -> Result<(), Vec<SchemaViolation>>;
+}
+
+/// Something wrong with a schema, at the JSON pointer to the offending part of it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SchemaViolation {
+    location: JsonPointer,
+    message: String,
+}
+
+impl SchemaViolation {
+    pub fn new(location: JsonPointer, message: impl Into<String>) -> Self {
+        SchemaViolation {
+            location,
+            message: message.into(),
+        }
+    }
+
+    pub fn location(&self) -> &JsonPointer {
+        &self.location
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for SchemaViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at '{}'", self.message, self.location)
+    }
+}
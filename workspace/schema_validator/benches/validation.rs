@@ -0,0 +1,83 @@
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use schema_registry::SchemaRegistry;
+use schema_validator::{CompiledValidator, Validator};
+use serde_json::{json, Value};
+use serde_json_schema::Schema;
+
+const SCHEMA: &str = r##"{
+    "$id": "https://example.com/event",
+    "type": "object",
+    "required": ["id", "timestamp", "tags", "attendees"],
+    "properties": {
+        "id": { "type": "string", "pattern": "^[a-f0-9]{8}$" },
+        "timestamp": { "type": "integer", "minimum": 0 },
+        "tags": {
+            "type": "array",
+            "items": { "type": "string", "minLength": 1 },
+            "uniqueItems": true
+        },
+        "attendees": {
+            "type": "array",
+            "items": { "$ref": "#/$defs/person" }
+        }
+    },
+    "additionalProperties": false,
+    "$defs": {
+        "person": {
+            "type": "object",
+            "required": ["name", "age"],
+            "properties": {
+                "name": { "type": "string", "maxLength": 64 },
+                "age": { "type": "integer", "minimum": 0, "exclusiveMaximum": 150 },
+                "email": { "type": "string", "pattern": "^[^@]+@[^@]+$" }
+            }
+        }
+    }
+}"##;
+
+fn event(age: i64) -> Value {
+    let attendees = (0..50)
+        .map(|index| json!({ "name": format!("Person {index}"), "age": age, "email": "a@b.c" }))
+        .collect::<Vec<Value>>();
+
+    json!({
+        "id": "deadbeef",
+        "timestamp": 1700000000,
+        "tags": ["conference", "rust", "schemas"],
+        "attendees": attendees
+    })
+}
+
+fn validation(c: &mut Criterion) {
+    let schema: Schema = serde_json::from_str(SCHEMA).unwrap();
+    let registry = SchemaRegistry::new()
+        .add_internally_identified_schema(schema)
+        .unwrap()
+        .discover()
+        .unwrap();
+    let schema = registry.get("https://example.com/event").unwrap();
+
+    let interpreter = Validator::new(schema, &registry);
+    let compiled = CompiledValidator::compile(schema, &registry).unwrap();
+
+    for (name, instance) in [("valid", event(42)), ("invalid", event(200))] {
+        let mut group = c.benchmark_group(name);
+
+        group.bench_function("interpretive", |b| {
+            b.iter(|| interpreter.validate(black_box(&instance)))
+        });
+        group.bench_function("compiled", |b| {
+            b.iter(|| compiled.validate(black_box(&instance)))
+        });
+        group.bench_function("compiled is_valid", |b| {
+            b.iter(|| compiled.is_valid(black_box(&instance)))
+        });
+
+        group.finish();
+    }
+}
+
+criterion_group!(benches, validation);
+criterion_main!(benches);
@@ -0,0 +1,1890 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::Display;
+use std::iter;
+
+use regex::Regex;
+use schema_discovery::append_to_path;
+use schema_registry::{dynamic_anchors, ResolvedSchema, SchemaRegistry};
+use serde_json::{Number, Value};
+use serde_json_schema::{
+    BooleanOrNumber, BooleanOrSchema, Dialect, Schema, SchemaOrSchemaArray, SchemaOrStringArray,
+    StringOrStringArray,
+};
+
+use crate::keywords::{
+    compare, escape, is_multiple_of, join_reference, json_equals, target_location, Limit,
+};
+use crate::{ValidationError, ValidationErrorKind};
+
+type NodeId = usize;
+
+const NULL: u8 = 1;
+const BOOLEAN: u8 = 1 << 1;
+const OBJECT: u8 = 1 << 2;
+const ARRAY: u8 = 1 << 3;
+const STRING: u8 = 1 << 4;
+const NUMBER: u8 = 1 << 5;
+const INTEGER: u8 = 1 << 6;
+
+/// A validator program compiled from a schema and the registry it references. References are
+/// resolved, regular expressions are compiled and every subschema is stored in a flat arena, so
+/// validating an instance never has to look anything up by name.
+pub struct CompiledValidator {
+    nodes: Vec<CompiledNode>,
+    tracks_annotations: bool,
+}
+
+struct CompiledNode {
+    absolute_location: Option<String>,
+    /// The `$dynamicAnchor`s of a node that starts a schema resource, `None` for other nodes.
+    dynamic_anchors: Option<Vec<(String, NodeId)>>,
+    /// Whether the node starts a schema resource that sets `$recursiveAnchor`.
+    recursive_anchor: bool,
+    kind: NodeKind,
+}
+
+enum NodeKind {
+    Boolean(bool),
+    Keywords(Vec<Keyword>),
+}
+
+enum Keyword {
+    Reference(NodeId),
+    /// The statically resolved target, and the anchor name when the dynamic scope may replace it.
+    DynamicReference(NodeId, Option<String>),
+    RecursiveReference(NodeId),
+    Type(u8, Vec<String>),
+    Enum(Vec<Value>),
+    Const(Value),
+    Minimum(Limit, bool),
+    ExclusiveMinimum(Limit),
+    Maximum(Limit, bool),
+    ExclusiveMaximum(Limit),
+    MultipleOf(Limit),
+    MinLength(i64),
+    MaxLength(i64),
+    Pattern(Regex),
+    PrefixItems(&'static str, Vec<NodeId>),
+    Items(&'static str, usize, NodeId),
+    Contains(NodeId, Option<i64>, Option<i64>),
+    MinItems(i64),
+    MaxItems(i64),
+    UniqueItems,
+    Properties(Vec<(String, NodeId)>),
+    PatternProperties(Vec<(String, Regex, NodeId)>),
+    AdditionalProperties(NodeId, HashSet<String>, Vec<Regex>),
+    PropertyNames(NodeId),
+    Required(Vec<String>),
+    MinProperties(i64),
+    MaxProperties(i64),
+    DependentRequired(&'static str, Vec<(String, Vec<String>)>),
+    DependentSchemas(&'static str, Vec<(String, NodeId)>),
+    AllOf(Vec<NodeId>),
+    AnyOf(Vec<NodeId>),
+    OneOf(Vec<NodeId>),
+    Not(NodeId),
+    Conditional(NodeId, Option<NodeId>, Option<NodeId>),
+    UnevaluatedItems(NodeId),
+    UnevaluatedProperties(NodeId),
+}
+
+impl Keyword {
+    /// Subschemas applied to the same instance location, through which a reference loop would
+    /// never terminate.
+    fn in_place(&self) -> Vec<NodeId> {
+        match self {
+            Keyword::Reference(node)
+            | Keyword::DynamicReference(node, _)
+            | Keyword::RecursiveReference(node)
+            | Keyword::Not(node) => vec![*node],
+            Keyword::AllOf(nodes) | Keyword::AnyOf(nodes) | Keyword::OneOf(nodes) => {
+                nodes.to_owned()
+            }
+            Keyword::Conditional(schema_if, schema_then, schema_else) => {
+                [Some(*schema_if), *schema_then, *schema_else]
+                    .into_iter()
+                    .flatten()
+                    .collect()
+            }
+            Keyword::DependentSchemas(_, nodes) => nodes.iter().map(|(_, node)| *node).collect(),
+            _ => Vec::new(),
+        }
+    }
+}
+
+impl CompiledValidator {
+    pub fn compile(
+        schema: &Schema,
+        registry: &SchemaRegistry,
+    ) -> Result<CompiledValidator, CompileError> {
+        let base = schema.get_id().unwrap_or_default();
+
+        let mut compiler = Compiler {
+            root: schema,
+            root_base: base.clone(),
+            registry,
+            nodes: Vec::new(),
+            compiled: HashMap::new(),
+            tracks_annotations: false,
+            dialects: Vec::new(),
+        };
+
+        compiler.schema(schema, &base, schema.get_id())?;
+        compiler.check_reference_loops()?;
+
+        Ok(CompiledValidator {
+            nodes: compiler.nodes,
+            tracks_annotations: compiler.tracks_annotations,
+        })
+    }
+
+    pub fn is_valid(&self, instance: &Value) -> bool {
+        Run::new(self, false).evaluate(0, instance, &mut Evaluated::default())
+    }
+
+    pub fn validate(&self, instance: &Value) -> Result<(), Vec<ValidationError>> {
+        let mut run = Run::new(self, true);
+        run.evaluate(0, instance, &mut Evaluated::default());
+
+        match run.errors {
+            Some(errors) if !errors.is_empty() => Err(errors),
+            _ => Ok(()),
+        }
+    }
+}
+
+struct Compiler<'a> {
+    root: &'a Schema,
+    root_base: String,
+    registry: &'a SchemaRegistry,
+    nodes: Vec<CompiledNode>,
+    compiled: HashMap<*const Schema, NodeId>,
+    tracks_annotations: bool,
+    /// The draft of each schema being compiled, declared by it or the nearest one declaring it.
+    dialects: Vec<Dialect>,
+}
+
+impl<'a> Compiler<'a> {
+    fn node(
+        &mut self,
+        node: &'a BooleanOrSchema,
+        base: &str,
+        absolute_location: Option<String>,
+    ) -> Result<NodeId, CompileError> {
+        match node {
+            BooleanOrSchema::Boolean(value) => {
+                self.nodes.push(CompiledNode {
+                    absolute_location,
+                    dynamic_anchors: None,
+                    recursive_anchor: false,
+                    kind: NodeKind::Boolean(*value),
+                });
+                Ok(self.nodes.len() - 1)
+            }
+            BooleanOrSchema::InnerSchema(schema) => self.schema(schema, base, absolute_location),
+        }
+    }
+
+    /// Compiles the schema a reference resolved to, whose base URI already accounts for its id.
+    fn target(&mut self, uri: &str, target: ResolvedSchema<'a>) -> Result<NodeId, CompileError> {
+        let location = target_location(uri, &target);
+
+        self.resolved(target.schema(), target.base_uri(), Some(location))
+    }
+
+    fn schema(
+        &mut self,
+        schema: &'a Schema,
+        base: &str,
+        absolute_location: Option<String>,
+    ) -> Result<NodeId, CompileError> {
+        match schema.get_id() {
+            Some(schema_id) => {
+                let base = join_reference(base, &schema_id);
+                self.resolved(schema, &base, Some(base.clone()))
+            }
+            None => self.resolved(schema, base, absolute_location),
+        }
+    }
+
+    /// Compiles a schema within a base URI that already accounts for the schema's id.
+    fn resolved(
+        &mut self,
+        schema: &'a Schema,
+        base: &str,
+        absolute_location: Option<String>,
+    ) -> Result<NodeId, CompileError> {
+        if let Some(id) = self.compiled.get(&(schema as *const Schema)) {
+            return Ok(*id);
+        }
+
+        // Reserve the slot up front so recursive references can point at it.
+        let id = self.nodes.len();
+        self.nodes.push(CompiledNode {
+            absolute_location: None,
+            dynamic_anchors: None,
+            recursive_anchor: false,
+            kind: NodeKind::Boolean(true),
+        });
+        self.compiled.insert(schema, id);
+
+        let is_resource = id == 0 || schema.get_id().is_some_and(|id| !id.starts_with('#'));
+        let base = base.to_owned();
+
+        let dialect = schema
+            .dialect()
+            .or(self.dialects.last().copied())
+            .unwrap_or(Dialect::Draft2020_12);
+        self.dialects.push(dialect);
+
+        let mut keywords = Vec::new();
+
+        if let Some(reference) = &schema.reference {
+            let uri = join_reference(&base, reference);
+            let target = self
+                .lookup(&uri)
+                .ok_or_else(|| CompileError::UnresolvableReference(reference.to_owned()))?;
+
+            keywords.push(Keyword::Reference(self.target(&uri, target)?));
+        }
+
+        if let Some(reference) = &schema.dynamic_reference {
+            let uri = join_reference(&base, reference);
+            let target = self
+                .lookup(&uri)
+                .ok_or_else(|| CompileError::UnresolvableReference(reference.to_owned()))?;
+
+            // Only a reference landing on a matching `$dynamicAnchor` consults the dynamic scope.
+            let name = reference
+                .split_once('#')
+                .map(|(_, fragment)| fragment)
+                .filter(|fragment| target.schema().dynamic_anchor.as_deref() == Some(*fragment))
+                .map(str::to_owned);
+
+            keywords.push(Keyword::DynamicReference(self.target(&uri, target)?, name));
+        }
+
+        if let Some(reference) = &schema.recursive_reference {
+            let uri = join_reference(&base, reference);
+            let target = self
+                .lookup(&uri)
+                .ok_or_else(|| CompileError::UnresolvableReference(reference.to_owned()))?;
+
+            keywords.push(Keyword::RecursiveReference(self.target(&uri, target)?));
+        }
+
+        // Drafts before 2019-09 ignore the keywords next to a `$ref`.
+        if schema.reference.is_none() || dialect > Dialect::Draft7 {
+            self.keywords(schema, &base, &absolute_location, &mut keywords)?;
+        }
+        self.dialects.pop();
+
+        let dynamic_anchors = match is_resource {
+            true => Some(
+                dynamic_anchors(schema)
+                    .into_iter()
+                    .map(|(name, anchor)| {
+                        let location = format!("{base}#{name}");
+                        Ok((name.to_owned(), self.schema(anchor, &base, Some(location))?))
+                    })
+                    .collect::<Result<Vec<(String, NodeId)>, CompileError>>()?,
+            ),
+            false => None,
+        };
+
+        self.nodes[id] = CompiledNode {
+            absolute_location,
+            dynamic_anchors,
+            recursive_anchor: is_resource && schema.recursive_anchor == Some(true),
+            kind: NodeKind::Keywords(keywords),
+        };
+
+        Ok(id)
+    }
+
+    fn keywords(
+        &mut self,
+        schema: &'a Schema,
+        base: &str,
+        absolute_location: &Option<String>,
+        keywords: &mut Vec<Keyword>,
+    ) -> Result<(), CompileError> {
+        let location = |path: &str| {
+            absolute_location
+                .as_ref()
+                .map(|location| append_to_path(location, path))
+        };
+
+        let type_names = match &schema.schema_type {
+            Some(StringOrStringArray::String(json_type)) => Some(vec![json_type.to_owned()]),
+            Some(StringOrStringArray::Array(json_types)) => Some(json_types.to_owned()),
+            None => None,
+        };
+
+        if let Some(type_names) = type_names {
+            let mask = type_names
+                .iter()
+                .map(|json_type| type_mask(json_type))
+                .fold(0, |mask, bit| mask | bit);
+
+            keywords.push(Keyword::Type(mask, type_names));
+        }
+
+        if let Some(schema_enum) = &schema.schema_enum {
+            let values = schema_enum
+                .iter()
+                .map(|value| serde_json::to_value(value).unwrap_or(Value::Null))
+                .collect();
+
+            keywords.push(Keyword::Enum(values));
+        }
+
+        if let Some(schema_const) = &schema.schema_const {
+            keywords.push(Keyword::Const(
+                serde_json::to_value(schema_const).unwrap_or(Value::Null),
+            ));
+        }
+
+        if let Some(minimum) = &schema.minimum {
+            let exclusive = matches!(
+                schema.exclusive_minimum,
+                Some(BooleanOrNumber::Boolean(true))
+            );
+
+            keywords.push(Keyword::Minimum(minimum.into(), exclusive));
+        }
+
+        if let Some(BooleanOrNumber::Number(value)) = &schema.exclusive_minimum {
+            keywords.push(Keyword::ExclusiveMinimum(value.into()));
+        }
+
+        if let Some(maximum) = &schema.maximum {
+            let exclusive = matches!(
+                schema.exlusive_maximum,
+                Some(BooleanOrNumber::Boolean(true))
+            );
+
+            keywords.push(Keyword::Maximum(maximum.into(), exclusive));
+        }
+
+        if let Some(BooleanOrNumber::Number(value)) = &schema.exlusive_maximum {
+            keywords.push(Keyword::ExclusiveMaximum(value.into()));
+        }
+
+        if let Some(multiple_of) = &schema.multiple_of {
+            keywords.push(Keyword::MultipleOf(multiple_of.into()));
+        }
+
+        if let Some(min_length) = schema.min_length {
+            keywords.push(Keyword::MinLength(min_length));
+        }
+
+        if let Some(max_length) = schema.max_length {
+            keywords.push(Keyword::MaxLength(max_length));
+        }
+
+        if let Some(pattern) = &schema.pattern {
+            keywords.push(Keyword::Pattern(regex(pattern)?));
+        }
+
+        let prefix_items = schema.prefix_items.as_deref().unwrap_or_default();
+
+        if !prefix_items.is_empty() {
+            let nodes = prefix_items
+                .iter()
+                .enumerate()
+                .map(|(index, prefix_schema)| {
+                    self.node(
+                        prefix_schema,
+                        base,
+                        location(&format!("prefixItems/{index}")),
+                    )
+                })
+                .collect::<Result<Vec<NodeId>, CompileError>>()?;
+
+            keywords.push(Keyword::PrefixItems("prefixItems", nodes));
+        }
+
+        match &schema.items {
+            Some(SchemaOrSchemaArray::Schema(items)) => {
+                let node = self.node(items, base, location("items"))?;
+                keywords.push(Keyword::Items("items", prefix_items.len(), node));
+            }
+            Some(SchemaOrSchemaArray::Array(items)) => {
+                let nodes = items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, item)| self.node(item, base, location(&format!("items/{index}"))))
+                    .collect::<Result<Vec<NodeId>, CompileError>>()?;
+                let skip = nodes.len();
+
+                keywords.push(Keyword::PrefixItems("items", nodes));
+
+                if let Some(additional_items) = &schema.additional_items {
+                    let node = self.node(additional_items, base, location("additionalItems"))?;
+                    keywords.push(Keyword::Items("additionalItems", skip, node));
+                }
+            }
+            None => {}
+        }
+
+        if let Some(contains) = &schema.contains {
+            let node = self.node(contains, base, location("contains"))?;
+            keywords.push(Keyword::Contains(
+                node,
+                schema.min_contains,
+                schema.max_contains,
+            ));
+        }
+
+        if let Some(min_items) = schema.min_items {
+            keywords.push(Keyword::MinItems(min_items));
+        }
+
+        if let Some(max_items) = schema.max_items {
+            keywords.push(Keyword::MaxItems(max_items));
+        }
+
+        if schema.unique_items == Some(true) {
+            keywords.push(Keyword::UniqueItems);
+        }
+
+        if let Some(properties) = &schema.properties {
+            let properties = properties
+                .iter()
+                .map(|(name, property_schema)| {
+                    let path = format!("properties/{}", escape(name));
+                    self.node(property_schema, base, location(&path))
+                        .map(|node| (name.to_owned(), node))
+                })
+                .collect::<Result<Vec<(String, NodeId)>, CompileError>>()?;
+
+            keywords.push(Keyword::Properties(properties));
+        }
+
+        if let Some(pattern_properties) = &schema.pattern_properties {
+            let pattern_properties = pattern_properties
+                .iter()
+                .map(|(pattern, pattern_schema)| {
+                    let path = format!("patternProperties/{}", escape(pattern));
+                    let node = self.node(pattern_schema, base, location(&path))?;
+                    Ok((pattern.to_owned(), regex(pattern)?, node))
+                })
+                .collect::<Result<Vec<(String, Regex, NodeId)>, CompileError>>()?;
+
+            keywords.push(Keyword::PatternProperties(pattern_properties));
+        }
+
+        if let Some(additional_properties) = &schema.additional_properties {
+            let node = self.node(
+                additional_properties,
+                base,
+                location("additionalProperties"),
+            )?;
+
+            let properties = schema
+                .properties
+                .iter()
+                .flat_map(|properties| properties.keys().cloned())
+                .collect();
+
+            let patterns = schema
+                .pattern_properties
+                .iter()
+                .flat_map(|pattern_properties| pattern_properties.keys())
+                .map(|pattern| regex(pattern))
+                .collect::<Result<Vec<Regex>, CompileError>>()?;
+
+            keywords.push(Keyword::AdditionalProperties(node, properties, patterns));
+        }
+
+        if let Some(property_names) = &schema.property_names {
+            let node = self.node(property_names, base, location("propertyNames"))?;
+            keywords.push(Keyword::PropertyNames(node));
+        }
+
+        if let Some(required) = &schema.required {
+            keywords.push(Keyword::Required(required.to_owned()));
+        }
+
+        if let Some(min_properties) = schema.min_properties {
+            keywords.push(Keyword::MinProperties(min_properties));
+        }
+
+        if let Some(max_properties) = schema.max_properties {
+            keywords.push(Keyword::MaxProperties(max_properties));
+        }
+
+        if let Some(dependent_required) = &schema.dependent_required {
+            keywords.push(Keyword::DependentRequired(
+                "dependentRequired",
+                dependent_required
+                    .iter()
+                    .map(|(property, dependencies)| (property.to_owned(), dependencies.to_owned()))
+                    .collect(),
+            ));
+        }
+
+        // Before 2019-09 `dependencies` holds both the required properties and the schemas.
+        if let Some(dependencies) = &schema.dependencies {
+            let dependent_required = dependencies
+                .iter()
+                .filter_map(|(property, dependency)| match dependency {
+                    SchemaOrStringArray::Array(dependencies) => {
+                        Some((property.to_owned(), dependencies.to_owned()))
+                    }
+                    SchemaOrStringArray::Schema(_) => None,
+                })
+                .collect::<Vec<(String, Vec<String>)>>();
+
+            if !dependent_required.is_empty() {
+                keywords.push(Keyword::DependentRequired(
+                    "dependencies",
+                    dependent_required,
+                ));
+            }
+        }
+
+        if let Some(dependent_schemas) = &schema.dependent_schemas {
+            let dependent_schemas = dependent_schemas
+                .iter()
+                .map(|(property, dependent_schema)| {
+                    let path = format!("dependentSchemas/{}", escape(property));
+                    self.node(dependent_schema, base, location(&path))
+                        .map(|node| (property.to_owned(), node))
+                })
+                .collect::<Result<Vec<(String, NodeId)>, CompileError>>()?;
+
+            keywords.push(Keyword::DependentSchemas(
+                "dependentSchemas",
+                dependent_schemas,
+            ));
+        }
+
+        if let Some(dependencies) = &schema.dependencies {
+            let dependent_schemas = dependencies
+                .iter()
+                .filter_map(|(property, dependency)| match dependency {
+                    SchemaOrStringArray::Schema(dependent_schema) => {
+                        Some((property, dependent_schema))
+                    }
+                    SchemaOrStringArray::Array(_) => None,
+                })
+                .map(|(property, dependent_schema)| {
+                    let path = format!("dependencies/{}", escape(property));
+                    self.node(dependent_schema, base, location(&path))
+                        .map(|node| (property.to_owned(), node))
+                })
+                .collect::<Result<Vec<(String, NodeId)>, CompileError>>()?;
+
+            if !dependent_schemas.is_empty() {
+                keywords.push(Keyword::DependentSchemas("dependencies", dependent_schemas));
+            }
+        }
+
+        let compositions = [
+            (
+                "allOf",
+                &schema.all_of,
+                Keyword::AllOf as fn(Vec<NodeId>) -> Keyword,
+            ),
+            ("anyOf", &schema.any_of, Keyword::AnyOf),
+            ("oneOf", &schema.one_of, Keyword::OneOf),
+        ];
+
+        for (keyword, subschemas, constructor) in compositions {
+            if let Some(subschemas) = subschemas {
+                let nodes = subschemas
+                    .iter()
+                    .enumerate()
+                    .map(|(index, subschema)| {
+                        self.node(subschema, base, location(&format!("{keyword}/{index}")))
+                    })
+                    .collect::<Result<Vec<NodeId>, CompileError>>()?;
+
+                keywords.push(constructor(nodes));
+            }
+        }
+
+        if let Some(not) = &schema.not {
+            keywords.push(Keyword::Not(self.node(not, base, location("not"))?));
+        }
+
+        if let Some(schema_if) = &schema.schema_if {
+            let schema_if = self.node(schema_if, base, location("if"))?;
+            let schema_then = match &schema.schema_then {
+                Some(schema_then) => Some(self.node(schema_then, base, location("then"))?),
+                None => None,
+            };
+            let schema_else = match &schema.schema_else {
+                Some(schema_else) => Some(self.node(schema_else, base, location("else"))?),
+                None => None,
+            };
+
+            keywords.push(Keyword::Conditional(schema_if, schema_then, schema_else));
+        }
+
+        if let Some(unevaluated_items) = &schema.unevaluated_items {
+            let node = self.node(unevaluated_items, base, location("unevaluatedItems"))?;
+            keywords.push(Keyword::UnevaluatedItems(node));
+            self.tracks_annotations = true;
+        }
+
+        if let Some(unevaluated_properties) = &schema.unevaluated_properties {
+            let node = self.node(
+                unevaluated_properties,
+                base,
+                location("unevaluatedProperties"),
+            )?;
+            keywords.push(Keyword::UnevaluatedProperties(node));
+            self.tracks_annotations = true;
+        }
+
+        Ok(())
+    }
+
+    fn lookup(&self, uri: &str) -> Option<ResolvedSchema<'a>> {
+        let uri = uri.strip_suffix('#').unwrap_or(uri);
+
+        self.registry.resolve(&self.root_base, uri).or_else(|| {
+            // The schema being validated need not be registered.
+            let (document, fragment) = uri.split_once('#').unwrap_or((uri, ""));
+            let root = ResolvedSchema::new(self.root, self.root_base.clone());
+
+            (document == self.root_base)
+                .then_some(root)?
+                .fragment(fragment)
+        })
+    }
+
+    /// Rejects schemas that would reference themselves without ever descending into the
+    /// instance, as validating against those could never finish.
+    fn check_reference_loops(&self) -> Result<(), CompileError> {
+        let mut states = vec![0_u8; self.nodes.len()];
+
+        for start in 0..self.nodes.len() {
+            if states[start] != 0 {
+                continue;
+            }
+
+            let mut stack = vec![(start, self.in_place(start), 0)];
+            states[start] = 1;
+
+            while let Some((node, edges, position)) = stack.last_mut() {
+                match edges.get(*position).copied() {
+                    Some(next) => {
+                        *position += 1;
+
+                        match states[next] {
+                            0 => {
+                                states[next] = 1;
+                                stack.push((next, self.in_place(next), 0));
+                            }
+                            1 => {
+                                let location = self.nodes[next]
+                                    .absolute_location
+                                    .clone()
+                                    .unwrap_or_default();
+                                return Err(CompileError::CyclicReference(location));
+                            }
+                            _ => {}
+                        }
+                    }
+                    None => {
+                        states[*node] = 2;
+                        stack.pop();
+                    }
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn in_place(&self, node: NodeId) -> Vec<NodeId> {
+        match &self.nodes[node].kind {
+            NodeKind::Keywords(keywords) => keywords
+                .iter()
+                .flat_map(|keyword| match keyword {
+                    // Any resource might end up in the dynamic scope, so any matching anchor
+                    // might be where the reference leads.
+                    Keyword::DynamicReference(target, Some(name)) => iter::once(*target)
+                        .chain(self.nodes.iter().flat_map(|node| {
+                            node.dynamic_anchors
+                                .iter()
+                                .flatten()
+                                .filter(|(anchor, _)| anchor == name)
+                                .map(|(_, anchor)| *anchor)
+                        }))
+                        .collect(),
+                    Keyword::RecursiveReference(target) if self.nodes[*target].recursive_anchor => {
+                        iter::once(*target)
+                            .chain(
+                                (0..self.nodes.len())
+                                    .filter(|node| self.nodes[*node].recursive_anchor),
+                            )
+                            .collect()
+                    }
+                    keyword => keyword.in_place(),
+                })
+                .collect(),
+            NodeKind::Boolean(_) => Vec::new(),
+        }
+    }
+}
+
+fn regex(pattern: &str) -> Result<Regex, CompileError> {
+    Regex::new(pattern).map_err(|_| CompileError::InvalidPattern(pattern.to_owned()))
+}
+
+fn type_mask(json_type: &str) -> u8 {
+    match json_type {
+        "null" => NULL,
+        "boolean" => BOOLEAN,
+        "object" => OBJECT,
+        "array" => ARRAY,
+        "string" => STRING,
+        "number" => NUMBER,
+        "integer" => INTEGER,
+        _ => 0,
+    }
+}
+
+fn instance_mask(instance: &Value) -> u8 {
+    match instance {
+        Value::Null => NULL,
+        Value::Bool(_) => BOOLEAN,
+        Value::Object(_) => OBJECT,
+        Value::Array(_) => ARRAY,
+        Value::String(_) => STRING,
+        Value::Number(number) => {
+            let integer = number.is_i64()
+                || number.is_u64()
+                || number.as_f64().is_some_and(|value| value.fract() == 0.0);
+
+            match integer {
+                true => NUMBER | INTEGER,
+                false => NUMBER,
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Segment<'a> {
+    Keyword(&'static str),
+    Name(&'a str),
+    Index(usize),
+}
+
+fn format_path(segments: &[Segment<'_>]) -> String {
+    segments
+        .iter()
+        .map(|segment| match segment {
+            Segment::Keyword(keyword) => format!("/{keyword}"),
+            Segment::Name(name) => format!("/{}", escape(name)),
+            Segment::Index(index) => format!("/{index}"),
+        })
+        .collect()
+}
+
+#[derive(Default)]
+struct Evaluated<'i> {
+    properties: HashSet<&'i str>,
+    items: HashSet<usize>,
+}
+
+impl<'i> Evaluated<'i> {
+    fn extend(&mut self, other: Evaluated<'i>) {
+        self.properties.extend(other.properties);
+        self.items.extend(other.items);
+    }
+}
+
+/// A single validation of an instance. Errors are only collected when asked for, otherwise the
+/// run stops at the first failing keyword.
+struct Run<'p, 'i> {
+    validator: &'p CompiledValidator,
+    errors: Option<Vec<ValidationError>>,
+    keyword_path: Vec<Segment<'p>>,
+    instance_path: Vec<Segment<'i>>,
+    /// The resource nodes entered so far, outermost first.
+    dynamic_scope: Vec<NodeId>,
+}
+
+impl<'p, 'i> Run<'p, 'i> {
+    fn new(validator: &'p CompiledValidator, collect_errors: bool) -> Self {
+        Run {
+            validator,
+            errors: collect_errors.then(Vec::new),
+            keyword_path: Vec::new(),
+            instance_path: Vec::new(),
+            dynamic_scope: Vec::new(),
+        }
+    }
+
+    fn evaluate(&mut self, id: NodeId, instance: &'i Value, evaluated: &mut Evaluated<'i>) -> bool {
+        let validator = self.validator;
+        let node = &validator.nodes[id];
+
+        let keywords = match &node.kind {
+            NodeKind::Boolean(true) => return true,
+            NodeKind::Boolean(false) => {
+                return self.fail(
+                    node,
+                    self.keyword_path.len(),
+                    ValidationErrorKind::FalseSchema,
+                )
+            }
+            NodeKind::Keywords(keywords) => keywords,
+        };
+
+        let start = self.keyword_path.len();
+        let mut valid = true;
+
+        if node.dynamic_anchors.is_some() {
+            self.dynamic_scope.push(id);
+        }
+
+        for keyword in keywords {
+            if !self.keyword(node, start, keyword, instance, evaluated) {
+                valid = false;
+
+                if self.errors.is_none() {
+                    break;
+                }
+            }
+        }
+
+        if node.dynamic_anchors.is_some() {
+            self.dynamic_scope.pop();
+        }
+
+        valid
+    }
+
+    /// The outermost `$dynamicAnchor` with the given name among the resources entered so far.
+    fn dynamic_anchor(&self, name: &str) -> Option<NodeId> {
+        self.dynamic_scope.iter().find_map(|resource| {
+            self.validator.nodes[*resource]
+                .dynamic_anchors
+                .iter()
+                .flatten()
+                .find(|(anchor, _)| anchor == name)
+                .map(|(_, node)| *node)
+        })
+    }
+
+    /// Where a `$recursiveRef` whose reference resolves to the target leads: the outermost of
+    /// the resources setting `$recursiveAnchor` entered right before it, if the target sets it.
+    fn recursive_anchor(&self, target: NodeId) -> NodeId {
+        if !self.validator.nodes[target].recursive_anchor {
+            return target;
+        }
+
+        self.dynamic_scope
+            .iter()
+            .rev()
+            .take_while(|resource| self.validator.nodes[**resource].recursive_anchor)
+            .last()
+            .copied()
+            .unwrap_or(target)
+    }
+
+    /// Evaluates without recording errors, for applicators whose subschemas may fail without
+    /// the keyword itself failing.
+    fn probe(&mut self, id: NodeId, instance: &'i Value, evaluated: &mut Evaluated<'i>) -> bool {
+        let errors = self.errors.take();
+        let valid = self.evaluate(id, instance, evaluated);
+        self.errors = errors;
+        valid
+    }
+
+    fn fail(&mut self, node: &CompiledNode, start: usize, kind: ValidationErrorKind) -> bool {
+        if let Some(errors) = &mut self.errors {
+            let local_path = format_path(&self.keyword_path[start..]);
+            let absolute_keyword_location = match local_path.strip_prefix('/') {
+                Some(local_path) => node
+                    .absolute_location
+                    .as_ref()
+                    .map(|location| append_to_path(location, local_path)),
+                None => node.absolute_location.clone(),
+            };
+
+            errors.push(ValidationError {
+                instance_location: format_path(&self.instance_path),
+                keyword_location: format_path(&self.keyword_path),
+                absolute_keyword_location,
+                kind,
+            });
+        }
+
+        false
+    }
+
+    fn assert(
+        &mut self,
+        node: &CompiledNode,
+        start: usize,
+        keyword: &'static str,
+        failure: Option<ValidationErrorKind>,
+    ) -> bool {
+        match failure {
+            Some(kind) => {
+                self.keyword_path.push(Segment::Keyword(keyword));
+                self.fail(node, start, kind);
+                self.keyword_path.pop();
+                false
+            }
+            None => true,
+        }
+    }
+
+    fn child(
+        &mut self,
+        keyword: &[Segment<'p>],
+        instance_segment: Segment<'i>,
+        id: NodeId,
+        instance: &'i Value,
+    ) -> bool {
+        self.keyword_path.extend_from_slice(keyword);
+        self.instance_path.push(instance_segment);
+        let valid = self.evaluate(id, instance, &mut Evaluated::default());
+        self.instance_path.pop();
+        self.keyword_path
+            .truncate(self.keyword_path.len() - keyword.len());
+        valid
+    }
+
+    fn in_place(
+        &mut self,
+        keyword: &[Segment<'p>],
+        id: NodeId,
+        instance: &'i Value,
+        evaluated: &mut Evaluated<'i>,
+    ) -> bool {
+        self.keyword_path.extend_from_slice(keyword);
+        let mut inner = Evaluated::default();
+        let valid = self.evaluate(id, instance, &mut inner);
+        self.keyword_path
+            .truncate(self.keyword_path.len() - keyword.len());
+
+        if valid {
+            evaluated.extend(inner);
+        }
+
+        valid
+    }
+
+    fn keyword(
+        &mut self,
+        node: &'p CompiledNode,
+        start: usize,
+        keyword: &'p Keyword,
+        instance: &'i Value,
+        evaluated: &mut Evaluated<'i>,
+    ) -> bool {
+        let tracks_annotations = self.validator.tracks_annotations;
+
+        match (keyword, instance) {
+            (Keyword::Reference(target), _) => {
+                self.in_place(&[Segment::Keyword("$ref")], *target, instance, evaluated)
+            }
+            (Keyword::DynamicReference(target, name), _) => {
+                let target = name
+                    .as_deref()
+                    .and_then(|name| self.dynamic_anchor(name))
+                    .unwrap_or(*target);
+
+                self.in_place(
+                    &[Segment::Keyword("$dynamicRef")],
+                    target,
+                    instance,
+                    evaluated,
+                )
+            }
+            (Keyword::RecursiveReference(target), _) => {
+                let target = self.recursive_anchor(*target);
+
+                self.in_place(
+                    &[Segment::Keyword("$recursiveRef")],
+                    target,
+                    instance,
+                    evaluated,
+                )
+            }
+            (Keyword::Type(mask, names), _) => {
+                let matches = mask & instance_mask(instance) != 0;
+                self.assert(
+                    node,
+                    start,
+                    "type",
+                    (!matches).then(|| ValidationErrorKind::Type(names.to_owned())),
+                )
+            }
+            (Keyword::Enum(values), _) => {
+                let matches = values.iter().any(|value| json_equals(value, instance));
+                self.assert(
+                    node,
+                    start,
+                    "enum",
+                    (!matches).then_some(ValidationErrorKind::Enum),
+                )
+            }
+            (Keyword::Const(value), _) => {
+                let matches = json_equals(value, instance);
+                self.assert(
+                    node,
+                    start,
+                    "const",
+                    (!matches).then_some(ValidationErrorKind::Const),
+                )
+            }
+            (Keyword::Minimum(minimum, exclusive), Value::Number(number)) => {
+                match compare(number, *minimum) {
+                    Some(Ordering::Less) => self.assert(
+                        node,
+                        start,
+                        "minimum",
+                        Some(ValidationErrorKind::Minimum((*minimum).into())),
+                    ),
+                    Some(Ordering::Equal) if *exclusive => self.assert(
+                        node,
+                        start,
+                        "exclusiveMinimum",
+                        Some(ValidationErrorKind::ExclusiveMinimum((*minimum).into())),
+                    ),
+                    _ => true,
+                }
+            }
+            (Keyword::ExclusiveMinimum(minimum), Value::Number(number)) => {
+                let reached = compare(number, *minimum) != Some(Ordering::Greater);
+                self.assert(
+                    node,
+                    start,
+                    "exclusiveMinimum",
+                    reached.then(|| ValidationErrorKind::ExclusiveMinimum((*minimum).into())),
+                )
+            }
+            (Keyword::Maximum(maximum, exclusive), Value::Number(number)) => {
+                match compare(number, *maximum) {
+                    Some(Ordering::Greater) => self.assert(
+                        node,
+                        start,
+                        "maximum",
+                        Some(ValidationErrorKind::Maximum((*maximum).into())),
+                    ),
+                    Some(Ordering::Equal) if *exclusive => self.assert(
+                        node,
+                        start,
+                        "exclusiveMaximum",
+                        Some(ValidationErrorKind::ExclusiveMaximum((*maximum).into())),
+                    ),
+                    _ => true,
+                }
+            }
+            (Keyword::ExclusiveMaximum(maximum), Value::Number(number)) => {
+                let reached = compare(number, *maximum) != Some(Ordering::Less);
+                self.assert(
+                    node,
+                    start,
+                    "exclusiveMaximum",
+                    reached.then(|| ValidationErrorKind::ExclusiveMaximum((*maximum).into())),
+                )
+            }
+            (Keyword::MultipleOf(divisor), Value::Number(number)) => {
+                let multiple = is_multiple_of(number, *divisor);
+                self.assert(
+                    node,
+                    start,
+                    "multipleOf",
+                    (!multiple).then(|| ValidationErrorKind::MultipleOf(Number::from(*divisor))),
+                )
+            }
+            (Keyword::MinLength(min_length), Value::String(string)) => {
+                let short = (string.chars().count() as i64) < *min_length;
+                self.assert(
+                    node,
+                    start,
+                    "minLength",
+                    short.then_some(ValidationErrorKind::MinLength(*min_length)),
+                )
+            }
+            (Keyword::MaxLength(max_length), Value::String(string)) => {
+                let long = (string.chars().count() as i64) > *max_length;
+                self.assert(
+                    node,
+                    start,
+                    "maxLength",
+                    long.then_some(ValidationErrorKind::MaxLength(*max_length)),
+                )
+            }
+            (Keyword::Pattern(regex), Value::String(string)) => {
+                let matches = regex.is_match(string);
+                self.assert(
+                    node,
+                    start,
+                    "pattern",
+                    (!matches).then(|| ValidationErrorKind::Pattern(regex.as_str().to_owned())),
+                )
+            }
+            (Keyword::PrefixItems(keyword, nodes), Value::Array(items)) => {
+                let mut valid = true;
+
+                for (index, (id, item)) in nodes.iter().zip(items).enumerate() {
+                    let keyword = [Segment::Keyword(keyword), Segment::Index(index)];
+
+                    if !self.child(&keyword, Segment::Index(index), *id, item) {
+                        valid = false;
+
+                        if self.errors.is_none() {
+                            return false;
+                        }
+                    }
+
+                    if tracks_annotations {
+                        evaluated.items.insert(index);
+                    }
+                }
+
+                valid
+            }
+            (Keyword::Items(keyword, skip, id), Value::Array(items)) => {
+                let mut valid = true;
+
+                for (index, item) in items.iter().enumerate().skip(*skip) {
+                    let keyword = [Segment::Keyword(keyword)];
+
+                    if !self.child(&keyword, Segment::Index(index), *id, item) {
+                        valid = false;
+
+                        if self.errors.is_none() {
+                            return false;
+                        }
+                    }
+
+                    if tracks_annotations {
+                        evaluated.items.insert(index);
+                    }
+                }
+
+                valid
+            }
+            (Keyword::Contains(id, min_contains, max_contains), Value::Array(items)) => {
+                let mut matches = 0;
+
+                for (index, item) in items.iter().enumerate() {
+                    self.instance_path.push(Segment::Index(index));
+                    let matched = self.probe(*id, item, &mut Evaluated::default());
+                    self.instance_path.pop();
+
+                    if matched {
+                        matches += 1;
+
+                        if tracks_annotations {
+                            evaluated.items.insert(index);
+                        }
+                    }
+                }
+
+                let mut valid = match min_contains {
+                    Some(min_contains) => self.assert(
+                        node,
+                        start,
+                        "minContains",
+                        (matches < *min_contains)
+                            .then_some(ValidationErrorKind::MinContains(*min_contains)),
+                    ),
+                    None => self.assert(
+                        node,
+                        start,
+                        "contains",
+                        (matches == 0).then_some(ValidationErrorKind::Contains),
+                    ),
+                };
+
+                if let Some(max_contains) = max_contains {
+                    valid &= self.assert(
+                        node,
+                        start,
+                        "maxContains",
+                        (matches > *max_contains)
+                            .then_some(ValidationErrorKind::MaxContains(*max_contains)),
+                    );
+                }
+
+                valid
+            }
+            (Keyword::MinItems(min_items), Value::Array(items)) => self.assert(
+                node,
+                start,
+                "minItems",
+                ((items.len() as i64) < *min_items)
+                    .then_some(ValidationErrorKind::MinItems(*min_items)),
+            ),
+            (Keyword::MaxItems(max_items), Value::Array(items)) => self.assert(
+                node,
+                start,
+                "maxItems",
+                ((items.len() as i64) > *max_items)
+                    .then_some(ValidationErrorKind::MaxItems(*max_items)),
+            ),
+            (Keyword::UniqueItems, Value::Array(items)) => {
+                let duplicate = items.iter().enumerate().any(|(index, item)| {
+                    items[index + 1..]
+                        .iter()
+                        .any(|other| json_equals(item, other))
+                });
+
+                self.assert(
+                    node,
+                    start,
+                    "uniqueItems",
+                    duplicate.then_some(ValidationErrorKind::UniqueItems),
+                )
+            }
+            (Keyword::Properties(properties), Value::Object(object)) => {
+                let mut valid = true;
+
+                for (name, id) in properties {
+                    let Some((key, value)) = object.get_key_value(name) else {
+                        continue;
+                    };
+
+                    let keyword = [Segment::Keyword("properties"), Segment::Name(name)];
+
+                    if !self.child(&keyword, Segment::Name(key), *id, value) {
+                        valid = false;
+
+                        if self.errors.is_none() {
+                            return false;
+                        }
+                    }
+
+                    if tracks_annotations {
+                        evaluated.properties.insert(key);
+                    }
+                }
+
+                valid
+            }
+            (Keyword::PatternProperties(patterns), Value::Object(object)) => {
+                let mut valid = true;
+
+                for (pattern, regex, id) in patterns {
+                    for (name, value) in object.iter().filter(|(name, _)| regex.is_match(name)) {
+                        let keyword = [
+                            Segment::Keyword("patternProperties"),
+                            Segment::Name(pattern),
+                        ];
+
+                        if !self.child(&keyword, Segment::Name(name), *id, value) {
+                            valid = false;
+
+                            if self.errors.is_none() {
+                                return false;
+                            }
+                        }
+
+                        if tracks_annotations {
+                            evaluated.properties.insert(name);
+                        }
+                    }
+                }
+
+                valid
+            }
+            (Keyword::AdditionalProperties(id, properties, patterns), Value::Object(object)) => {
+                let mut valid = true;
+
+                let additional = object.iter().filter(|(name, _)| {
+                    !properties.contains(name.as_str())
+                        && !patterns.iter().any(|regex| regex.is_match(name))
+                });
+
+                for (name, value) in additional {
+                    let keyword = [Segment::Keyword("additionalProperties")];
+
+                    if !self.child(&keyword, Segment::Name(name), *id, value) {
+                        valid = false;
+
+                        if self.errors.is_none() {
+                            return false;
+                        }
+                    }
+
+                    if tracks_annotations {
+                        evaluated.properties.insert(name);
+                    }
+                }
+
+                valid
+            }
+            (Keyword::PropertyNames(id), Value::Object(object)) => {
+                let mut valid = true;
+
+                for name in object.keys() {
+                    // Names aren't part of the instance, so each one is checked in a run of its
+                    // own and reported as a whole.
+                    let name_instance = Value::String(name.to_owned());
+                    let matches = Run::new(self.validator, false).evaluate(
+                        *id,
+                        &name_instance,
+                        &mut Evaluated::default(),
+                    );
+
+                    if !matches {
+                        valid = self.assert(
+                            node,
+                            start,
+                            "propertyNames",
+                            Some(ValidationErrorKind::PropertyNames(name.to_owned())),
+                        );
+
+                        if self.errors.is_none() {
+                            return false;
+                        }
+                    }
+                }
+
+                valid
+            }
+            (Keyword::Required(required), Value::Object(object)) => {
+                let mut valid = true;
+
+                for property in required
+                    .iter()
+                    .filter(|property| !object.contains_key(*property))
+                {
+                    valid = self.assert(
+                        node,
+                        start,
+                        "required",
+                        Some(ValidationErrorKind::Required(property.to_owned())),
+                    );
+
+                    if self.errors.is_none() {
+                        return false;
+                    }
+                }
+
+                valid
+            }
+            (Keyword::MinProperties(min_properties), Value::Object(object)) => self.assert(
+                node,
+                start,
+                "minProperties",
+                ((object.len() as i64) < *min_properties)
+                    .then_some(ValidationErrorKind::MinProperties(*min_properties)),
+            ),
+            (Keyword::MaxProperties(max_properties), Value::Object(object)) => self.assert(
+                node,
+                start,
+                "maxProperties",
+                ((object.len() as i64) > *max_properties)
+                    .then_some(ValidationErrorKind::MaxProperties(*max_properties)),
+            ),
+            (Keyword::DependentRequired(keyword, dependent_required), Value::Object(object)) => {
+                let mut valid = true;
+
+                for (property, dependencies) in dependent_required {
+                    if !object.contains_key(property) {
+                        continue;
+                    }
+
+                    for dependency in dependencies
+                        .iter()
+                        .filter(|dependency| !object.contains_key(*dependency))
+                    {
+                        self.keyword_path.push(Segment::Keyword(keyword));
+                        self.keyword_path.push(Segment::Name(property));
+                        valid = self.fail(
+                            node,
+                            start,
+                            ValidationErrorKind::DependentRequired(
+                                property.to_owned(),
+                                dependency.to_owned(),
+                            ),
+                        );
+                        self.keyword_path.truncate(self.keyword_path.len() - 2);
+
+                        if self.errors.is_none() {
+                            return false;
+                        }
+                    }
+                }
+
+                valid
+            }
+            (Keyword::DependentSchemas(keyword, dependent_schemas), Value::Object(object)) => {
+                let mut valid = true;
+
+                for (property, id) in dependent_schemas {
+                    if !object.contains_key(property) {
+                        continue;
+                    }
+
+                    let keyword = [Segment::Keyword(keyword), Segment::Name(property)];
+
+                    if !self.in_place(&keyword, *id, instance, evaluated) {
+                        valid = false;
+
+                        if self.errors.is_none() {
+                            return false;
+                        }
+                    }
+                }
+
+                valid
+            }
+            (Keyword::AllOf(nodes), _) => {
+                let mut valid = true;
+
+                for (index, id) in nodes.iter().enumerate() {
+                    let keyword = [Segment::Keyword("allOf"), Segment::Index(index)];
+
+                    if !self.in_place(&keyword, *id, instance, evaluated) {
+                        valid = false;
+
+                        if self.errors.is_none() {
+                            return false;
+                        }
+                    }
+                }
+
+                valid
+            }
+            (Keyword::AnyOf(nodes), _) => {
+                let mut matched = false;
+
+                for id in nodes {
+                    let mut inner = Evaluated::default();
+
+                    if self.probe(*id, instance, &mut inner) {
+                        matched = true;
+                        evaluated.extend(inner);
+
+                        if !tracks_annotations {
+                            break;
+                        }
+                    }
+                }
+
+                self.assert(
+                    node,
+                    start,
+                    "anyOf",
+                    (!matched).then_some(ValidationErrorKind::AnyOf),
+                )
+            }
+            (Keyword::OneOf(nodes), _) => {
+                let mut matches = 0;
+                let mut matched = Evaluated::default();
+
+                for id in nodes {
+                    let mut inner = Evaluated::default();
+
+                    if self.probe(*id, instance, &mut inner) {
+                        matches += 1;
+                        matched = inner;
+                    }
+                }
+
+                if matches == 1 {
+                    evaluated.extend(matched);
+                }
+
+                self.assert(
+                    node,
+                    start,
+                    "oneOf",
+                    (matches != 1).then_some(ValidationErrorKind::OneOf(matches)),
+                )
+            }
+            (Keyword::Not(id), _) => {
+                let matched = self.probe(*id, instance, &mut Evaluated::default());
+                self.assert(
+                    node,
+                    start,
+                    "not",
+                    matched.then_some(ValidationErrorKind::Not),
+                )
+            }
+            (Keyword::Conditional(schema_if, schema_then, schema_else), _) => {
+                let mut inner = Evaluated::default();
+
+                let (keyword, branch) = match self.probe(*schema_if, instance, &mut inner) {
+                    true => {
+                        evaluated.extend(inner);
+                        ("then", schema_then)
+                    }
+                    false => ("else", schema_else),
+                };
+
+                match branch {
+                    Some(id) => {
+                        self.in_place(&[Segment::Keyword(keyword)], *id, instance, evaluated)
+                    }
+                    None => true,
+                }
+            }
+            (Keyword::UnevaluatedItems(id), Value::Array(items)) => {
+                let mut valid = true;
+
+                for (index, item) in items.iter().enumerate() {
+                    if evaluated.items.contains(&index) {
+                        continue;
+                    }
+
+                    let keyword = [Segment::Keyword("unevaluatedItems")];
+
+                    if !self.child(&keyword, Segment::Index(index), *id, item) {
+                        valid = false;
+
+                        if self.errors.is_none() {
+                            return false;
+                        }
+                    }
+                }
+
+                evaluated.items.extend(0..items.len());
+                valid
+            }
+            (Keyword::UnevaluatedProperties(id), Value::Object(object)) => {
+                let mut valid = true;
+
+                for (name, value) in object {
+                    if evaluated.properties.contains(name.as_str()) {
+                        continue;
+                    }
+
+                    let keyword = [Segment::Keyword("unevaluatedProperties")];
+
+                    if !self.child(&keyword, Segment::Name(name), *id, value) {
+                        valid = false;
+
+                        if self.errors.is_none() {
+                            return false;
+                        }
+                    }
+                }
+
+                evaluated
+                    .properties
+                    .extend(object.keys().map(String::as_str));
+                valid
+            }
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CompileError {
+    UnresolvableReference(String),
+    InvalidPattern(String),
+    CyclicReference(String),
+}
+
+impl Error for CompileError {}
+
+impl Display for CompileError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnresolvableReference(reference) => {
+                write!(f, "Reference {} is not resolvable", reference)
+            }
+            Self::InvalidPattern(pattern) => {
+                write!(f, "Pattern {} is not a valid regular expression", pattern)
+            }
+            Self::CyclicReference(location) => {
+                write!(
+                    f,
+                    "Schema at {} references itself without consuming the instance",
+                    location
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::Validator;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn compiled_validator_is_shareable_between_threads() {
+        assert_send_sync::<CompiledValidator>();
+    }
+
+    /// https://json-schema.org/learn/miscellaneous-examples#arrays-of-things
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-dynamic-references-with-dyn
+    #[test]
+    fn dynamic_reference_example() {
+        let tree: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/tree",
+            "$dynamicAnchor": "node",
+            "type": "object",
+            "properties": {
+                "data": true,
+                "children": { "type": "array", "items": { "$dynamicRef": "#node" } }
+            }
+        }))
+        .unwrap();
+        let strict_tree: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/strict-tree",
+            "$dynamicAnchor": "node",
+            "$ref": "tree",
+            "unevaluatedProperties": false
+        }))
+        .unwrap();
+
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(tree)
+            .unwrap()
+            .add_internally_identified_schema(strict_tree)
+            .unwrap();
+        let instances = [
+            json!({ "children": [{ "data": 1, "children": [] }] }),
+            json!({ "children": [{ "daat": 1 }] }),
+            json!({ "children": [{ "children": [{ "daat": 1 }] }] }),
+        ];
+
+        for (uri, valid) in [
+            ("https://example.com/tree", [true, true, true]),
+            ("https://example.com/strict-tree", [true, false, false]),
+        ] {
+            let schema = registry.get(uri).unwrap();
+            let validator = CompiledValidator::compile(schema, &registry).unwrap();
+            let interpreter = Validator::new(schema, &registry);
+
+            for (instance, valid) in instances.iter().zip(valid) {
+                assert_eq!(validator.is_valid(instance), valid);
+                assert_eq!(validator.validate(instance), interpreter.validate(instance));
+            }
+        }
+    }
+
+    #[test]
+    fn reference_example() {
+        let schema: Schema = serde_json::from_str(
+            r##"{
+                "$id": "https://example.com/arrays.schema.json",
+                "type": "object",
+                "properties": {
+                    "vegetables": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/veggie" }
+                    },
+                    "children": {
+                        "type": "array",
+                        "items": { "$ref": "#" }
+                    }
+                },
+                "$defs": {
+                    "veggie": {
+                        "type": "object",
+                        "required": [ "veggieName", "veggieLike" ],
+                        "properties": {
+                            "veggieName": { "type": "string" },
+                            "veggieLike": { "type": "boolean" }
+                        }
+                    }
+                }
+            }"##,
+        )
+        .unwrap();
+
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(schema)
+            .unwrap()
+            .discover()
+            .unwrap();
+        let schema = registry
+            .get("https://example.com/arrays.schema.json")
+            .unwrap();
+        let validator = CompiledValidator::compile(schema, &registry).unwrap();
+        let interpreter = Validator::new(schema, &registry);
+
+        let valid = json!({
+            "vegetables": [{ "veggieName": "potato", "veggieLike": true }],
+            "children": [{ "vegetables": [] }]
+        });
+        let invalid = json!({
+            "vegetables": [{ "veggieName": 42, "veggieLike": true }],
+            "children": [{ "vegetables": [{ "veggieName": "broccoli" }] }]
+        });
+
+        assert!(validator.is_valid(&valid));
+        assert!(!validator.is_valid(&invalid));
+        assert_eq!(validator.validate(&valid), interpreter.validate(&valid));
+        assert_eq!(validator.validate(&invalid), interpreter.validate(&invalid));
+
+        let errors = validator.validate(&invalid).unwrap_err();
+        let required = errors
+            .iter()
+            .find(|error| error.instance_location() == "/children/0/vegetables/0")
+            .unwrap();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            required.absolute_keyword_location(),
+            Some("https://example.com/arrays.schema.json#/$defs/veggie/required")
+        );
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/object#unevaluatedproperties
+    #[test]
+    fn unevaluated_properties_example() {
+        let schema: Schema = serde_json::from_str(
+            r#"{
+                "allOf": [
+                    {
+                        "type": "object",
+                        "properties": {
+                            "street_address": { "type": "string" },
+                            "city": { "type": "string" },
+                            "state": { "type": "string" }
+                        },
+                        "required": ["street_address", "city", "state"]
+                    }
+                ],
+                "anyOf": [
+                    { "properties": { "type": { "const": "business" } }, "required": ["type"] },
+                    { "properties": { "type": { "const": "residential" } }, "required": ["type"] }
+                ],
+                "unevaluatedProperties": false
+            }"#,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = CompiledValidator::compile(&schema, &registry).unwrap();
+        let interpreter = Validator::new(&schema, &registry);
+
+        let instances = [
+            json!({
+                "street_address": "1600 Pennsylvania Avenue NW",
+                "city": "Washington",
+                "state": "DC",
+                "type": "business"
+            }),
+            json!({
+                "street_address": "1600 Pennsylvania Avenue NW",
+                "city": "Washington",
+                "state": "DC",
+                "type": "business",
+                "something that doesn't belong": "hi!"
+            }),
+            json!({ "city": "Washington", "type": "embassy" }),
+        ];
+
+        for instance in instances {
+            assert_eq!(
+                validator.validate(&instance),
+                interpreter.validate(&instance)
+            );
+            assert_eq!(
+                validator.is_valid(&instance),
+                interpreter.is_valid(&instance)
+            );
+        }
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/array#items
+    #[test]
+    fn legacy_keywords_example() {
+        let schema: Schema = serde_json::from_str(
+            r#"{
+                "type": "object",
+                "propertyNames": { "maxLength": 8 },
+                "properties": {
+                    "address": {
+                        "type": "array",
+                        "items": [
+                            { "type": "number", "exclusiveMinimum": 0 },
+                            { "type": "string" }
+                        ],
+                        "additionalItems": { "enum": ["Street", "Avenue", "Boulevard"] }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = CompiledValidator::compile(&schema, &registry).unwrap();
+        let interpreter = Validator::new(&schema, &registry);
+
+        let instances = [
+            json!({ "address": [1600, "Pennsylvania", "Avenue"] }),
+            json!({ "address": [0, "Pennsylvania", "Road"] }),
+            json!({ "address": [1600], "postal code": "20500" }),
+        ];
+
+        for instance in instances {
+            assert_eq!(
+                validator.validate(&instance),
+                interpreter.validate(&instance)
+            );
+            assert_eq!(
+                validator.is_valid(&instance),
+                interpreter.is_valid(&instance)
+            );
+        }
+
+        let errors = validator
+            .validate(&json!({ "address": [0, "Pennsylvania", "Road"] }))
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            errors[0].keyword_location(),
+            "/properties/address/items/0/exclusiveMinimum"
+        );
+        assert_eq!(
+            errors[1].keyword_location(),
+            "/properties/address/additionalItems/enum"
+        );
+    }
+
+    #[test]
+    fn compile_errors() {
+        let registry = SchemaRegistry::new();
+
+        let schema: Schema = serde_json::from_str(r##"{ "$ref": "#/$defs/missing" }"##).unwrap();
+
+        assert!(matches!(
+            CompiledValidator::compile(&schema, &registry),
+            Err(CompileError::UnresolvableReference(_))
+        ));
+
+        let schema: Schema = serde_json::from_str(r#"{ "pattern": "^(unclosed" }"#).unwrap();
+
+        assert!(matches!(
+            CompiledValidator::compile(&schema, &registry),
+            Err(CompileError::InvalidPattern(_))
+        ));
+
+        let schema: Schema = serde_json::from_str(
+            r##"{ "$id": "https://example.com/loop", "allOf": [{ "$ref": "#" }] }"##,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(schema)
+            .unwrap()
+            .discover()
+            .unwrap();
+        let schema = registry.get("https://example.com/loop").unwrap();
+
+        assert!(matches!(
+            CompiledValidator::compile(schema, &registry),
+            Err(CompileError::CyclicReference(_))
+        ));
+    }
+}
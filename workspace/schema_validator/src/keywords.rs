@@ -0,0 +1,171 @@
+use std::cmp::Ordering;
+
+use schema_discovery::resolve_uri;
+use schema_registry::ResolvedSchema;
+use serde_json::{Number, Value};
+use serde_json_schema::AnyType;
+
+#[derive(Clone, Copy)]
+pub(crate) enum Limit {
+    Integer(i64),
+    Number(f64),
+}
+
+impl Limit {
+    pub(crate) fn as_f64(self) -> f64 {
+        match self {
+            Limit::Integer(value) => value as f64,
+            Limit::Number(value) => value,
+        }
+    }
+}
+
+impl From<&serde_json_schema::Number> for Limit {
+    fn from(value: &serde_json_schema::Number) -> Self {
+        match value.as_i64() {
+            Some(value) => Limit::Integer(value),
+            None => Limit::Number(value.as_f64().unwrap_or(f64::NAN)),
+        }
+    }
+}
+
+impl From<Limit> for Number {
+    fn from(value: Limit) -> Self {
+        match value {
+            Limit::Integer(value) => Number::from(value),
+            Limit::Number(value) => Number::from_f64(value).unwrap_or(Number::from(0)),
+        }
+    }
+}
+
+pub(crate) fn compare(number: &Number, limit: Limit) -> Option<Ordering> {
+    match (number.as_i64(), limit) {
+        (Some(value), Limit::Integer(limit)) => Some(value.cmp(&limit)),
+        _ => number.as_f64()?.partial_cmp(&limit.as_f64()),
+    }
+}
+
+pub(crate) fn is_multiple_of(number: &Number, divisor: Limit) -> bool {
+    match (number.as_i64(), divisor) {
+        // `i64::MIN % -1` overflows, though every integer is a multiple of -1.
+        (Some(value), Limit::Integer(divisor)) if divisor != 0 => value
+            .checked_rem(divisor)
+            .is_none_or(|remainder| remainder == 0),
+        _ => {
+            let quotient = number.as_f64().unwrap_or(f64::NAN) / divisor.as_f64();
+
+            quotient.is_finite()
+                && (quotient - quotient.round()).abs() <= f64::EPSILON * quotient.abs().max(1.0)
+        }
+    }
+}
+
+pub(crate) fn is_of_type(instance: &Value, json_type: &str) -> bool {
+    match json_type {
+        "null" => instance.is_null(),
+        "boolean" => instance.is_boolean(),
+        "object" => instance.is_object(),
+        "array" => instance.is_array(),
+        "string" => instance.is_string(),
+        "number" => instance.is_number(),
+        "integer" => match instance {
+            Value::Number(number) => {
+                number.is_i64()
+                    || number.is_u64()
+                    || number.as_f64().is_some_and(|value| value.fract() == 0.0)
+            }
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+pub(crate) fn numbers_equal(left: &Number, right: &Number) -> bool {
+    match (left.as_i64(), right.as_i64()) {
+        (Some(left), Some(right)) => left == right,
+        _ => match (left.as_u64(), right.as_u64()) {
+            (Some(left), Some(right)) => left == right,
+            _ => left.as_f64() == right.as_f64(),
+        },
+    }
+}
+
+/// Structural equality as JSON Schema defines it: numbers compare by value and object keys are
+/// unordered.
+pub(crate) fn json_equals(left: &Value, right: &Value) -> bool {
+    match (left, right) {
+        (Value::Number(left), Value::Number(right)) => numbers_equal(left, right),
+        (Value::Array(left), Value::Array(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .zip(right)
+                    .all(|(left, right)| json_equals(left, right))
+        }
+        (Value::Object(left), Value::Object(right)) => {
+            left.len() == right.len()
+                && left
+                    .iter()
+                    .all(|(key, left)| right.get(key).is_some_and(|right| json_equals(left, right)))
+        }
+        _ => left == right,
+    }
+}
+
+pub(crate) fn any_type_equals(expected: &AnyType, instance: &Value) -> bool {
+    match (expected, instance) {
+        (AnyType::String(expected), Value::String(instance)) => expected == instance,
+        (AnyType::Boolean(expected), Value::Bool(instance)) => expected == instance,
+        (AnyType::Null, Value::Null) => true,
+        (AnyType::Number(expected), Value::Number(instance)) => {
+            numbers_equal(&expected.clone().into(), instance)
+        }
+        (AnyType::Array(expected), Value::Array(instance)) => {
+            expected.len() == instance.len()
+                && expected
+                    .iter()
+                    .zip(instance)
+                    .all(|(expected, instance)| any_type_equals(expected, instance))
+        }
+        (AnyType::Object(expected), Value::Object(instance)) => {
+            expected.len() == instance.len()
+                && expected.iter().all(|(key, expected)| {
+                    instance
+                        .get(key)
+                        .is_some_and(|instance| any_type_equals(expected, instance))
+                })
+        }
+        _ => false,
+    }
+}
+
+/// Escapes a reference token as described in RFC 6901.
+pub(crate) fn escape(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+pub(crate) fn join_reference(base: &str, reference: &str) -> String {
+    let uri = resolve_uri(base, reference);
+
+    match uri.strip_suffix('#') {
+        Some(document) => document.to_owned(),
+        None => uri,
+    }
+}
+
+/// The absolute location of the schema a reference resolved to. An embedded resource is located
+/// by its own id and a plain name by the resource declaring it, while a JSON pointer stays
+/// relative to the document the reference names.
+pub(crate) fn target_location(uri: &str, target: &ResolvedSchema<'_>) -> String {
+    let is_resource = target
+        .schema()
+        .get_id()
+        .is_some_and(|id| !id.starts_with('#'));
+
+    match uri.split_once('#') {
+        _ if is_resource => target.base_uri().to_owned(),
+        Some((_, fragment)) if fragment.starts_with('/') => uri.to_owned(),
+        Some((_, name)) => format!("{}#{name}", target.base_uri()),
+        None => target.base_uri().to_owned(),
+    }
+}
@@ -0,0 +1,2518 @@
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::error::Error;
+use std::fmt::Display;
+use std::ptr;
+
+use regex::Regex;
+use schema_discovery::append_to_path;
+use schema_registry::{DynamicScope, ResolvedSchema, SchemaRegistry};
+use serde_json::{Number, Value};
+use serde_json_schema::{
+    BooleanOrNumber, BooleanOrSchema, Dialect, Schema, SchemaOrSchemaArray, SchemaOrStringArray,
+    StringOrStringArray,
+};
+
+mod compiled;
+mod keywords;
+mod metaschema;
+mod output;
+
+pub use compiled::{CompileError, CompiledValidator};
+pub use metaschema::MetaSchemaValidator;
+pub use output::{OutputFormat, OutputUnit};
+
+use keywords::{
+    any_type_equals, compare, escape, is_multiple_of, is_of_type, join_reference, json_equals,
+    target_location, Limit,
+};
+use output::Node;
+
+pub struct Validator<'a> {
+    schema: &'a Schema,
+    registry: &'a SchemaRegistry,
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(schema: &'a Schema, registry: &'a SchemaRegistry) -> Self {
+        Validator { schema, registry }
+    }
+
+    pub fn validate(&self, instance: &Value) -> Result<(), Vec<ValidationError>> {
+        let node = self.evaluate(instance, false);
+
+        let mut errors = Vec::new();
+        node.collect_errors(&mut errors);
+
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(errors),
+        }
+    }
+
+    pub fn is_valid(&self, instance: &Value) -> bool {
+        self.evaluate(instance, false).valid
+    }
+
+    /// Validates the instance and reports the outcome in one of the standardised output formats.
+    pub fn output(&self, instance: &Value, format: OutputFormat) -> OutputUnit {
+        let record_passing = matches!(format, OutputFormat::Basic | OutputFormat::Verbose);
+        self.evaluate(instance, record_passing).output(format)
+    }
+
+    fn evaluate(&self, instance: &Value, record_passing: bool) -> Node {
+        let base = self.schema.get_id().unwrap_or_default();
+        let scope = Scope {
+            instance_location: String::new(),
+            keyword_location: String::new(),
+            absolute_keyword_location: self.schema.get_id(),
+            base: base.clone(),
+            dialect: Dialect::Draft2020_12,
+        };
+
+        let mut evaluator = Evaluator {
+            root: self.schema,
+            root_base: base,
+            registry: self.registry,
+            record_passing,
+            patterns: HashMap::new(),
+            references: Vec::new(),
+            dynamic_scope: DynamicScope::new(),
+        };
+
+        evaluator.evaluate(self.schema, instance, &scope).node
+    }
+}
+
+struct Evaluation {
+    node: Node,
+    record_passing: bool,
+    evaluated_properties: HashSet<String>,
+    evaluated_items: HashSet<usize>,
+}
+
+impl Evaluation {
+    fn new(scope: &Scope, record_passing: bool) -> Self {
+        Evaluation {
+            node: scope.node(None),
+            record_passing,
+            evaluated_properties: HashSet::new(),
+            evaluated_items: HashSet::new(),
+        }
+    }
+
+    fn is_valid(&self) -> bool {
+        self.node.valid
+    }
+
+    fn push(&mut self, node: Node) {
+        self.node.valid &= node.valid;
+        self.node.children.push(node);
+    }
+
+    fn fail(&mut self, scope: &Scope, keyword: &str, kind: ValidationErrorKind) {
+        self.push(scope.in_place(keyword).node(Some(kind)));
+    }
+
+    fn assert(&mut self, scope: &Scope, keyword: &str, failure: Option<ValidationErrorKind>) {
+        match failure {
+            Some(kind) => self.fail(scope, keyword, kind),
+            None if self.record_passing => self.push(scope.in_place(keyword).node(None)),
+            None => {}
+        }
+    }
+
+    fn annotate(&mut self, scope: &Scope, keyword: &str, annotation: Option<Value>) {
+        if self.record_passing {
+            let mut node = scope.in_place(keyword).node(None);
+            node.annotation = annotation;
+            self.push(node);
+        }
+    }
+
+    /// Records a keyword that applies subschemas, failing when the keyword itself failed or any
+    /// of the given subschema results did.
+    fn apply(
+        &mut self,
+        scope: &Scope,
+        keyword: &str,
+        children: Vec<Node>,
+        failure: Option<ValidationErrorKind>,
+        annotation: Option<Value>,
+    ) {
+        let valid = failure.is_none() && children.iter().all(|child| child.valid);
+
+        if valid && !self.record_passing {
+            return;
+        }
+
+        let mut node = scope.in_place(keyword).node(failure);
+        node.valid = valid;
+        node.children = children;
+        node.annotation = annotation.filter(|_| valid);
+
+        self.push(node);
+    }
+
+    /// Takes over the annotations of a subschema applied to the same instance location. These
+    /// are only collected from subschemas that passed, as the specification demands.
+    fn absorb(&mut self, other: &Evaluation) {
+        if other.is_valid() {
+            self.evaluated_properties
+                .extend(other.evaluated_properties.iter().cloned());
+            self.evaluated_items.extend(other.evaluated_items.iter());
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Scope {
+    instance_location: String,
+    keyword_location: String,
+    absolute_keyword_location: Option<String>,
+    base: String,
+    /// The draft declared by the nearest schema that declares one.
+    dialect: Dialect,
+}
+
+impl Scope {
+    fn in_place(&self, keyword_path: &str) -> Scope {
+        Scope {
+            instance_location: self.instance_location.clone(),
+            keyword_location: format!("{}/{}", self.keyword_location, keyword_path),
+            absolute_keyword_location: self
+                .absolute_keyword_location
+                .as_ref()
+                .map(|location| append_to_path(location, keyword_path)),
+            base: self.base.clone(),
+            dialect: self.dialect,
+        }
+    }
+
+    fn descend(&self, instance_token: &str, keyword_path: &str) -> Scope {
+        Scope {
+            instance_location: format!("{}/{}", self.instance_location, escape(instance_token)),
+            ..self.in_place(keyword_path)
+        }
+    }
+
+    fn node(&self, kind: Option<ValidationErrorKind>) -> Node {
+        Node {
+            valid: kind.is_none(),
+            keyword_location: self.keyword_location.clone(),
+            absolute_keyword_location: self.absolute_keyword_location.clone(),
+            instance_location: self.instance_location.clone(),
+            kind,
+            annotation: None,
+            children: Vec::new(),
+            annotation_only: false,
+        }
+    }
+}
+
+struct Evaluator<'a> {
+    root: &'a Schema,
+    root_base: String,
+    registry: &'a SchemaRegistry,
+    record_passing: bool,
+    patterns: HashMap<&'a str, Option<Regex>>,
+    references: Vec<(&'a Schema, String)>,
+    dynamic_scope: DynamicScope<'a>,
+}
+
+impl<'a> Evaluator<'a> {
+    fn evaluate(&mut self, schema: &'a Schema, instance: &Value, scope: &Scope) -> Evaluation {
+        let rebased;
+        let scope = match schema.get_id() {
+            Some(id) => {
+                let base = join_reference(&scope.base, &id);
+                rebased = Scope {
+                    instance_location: scope.instance_location.clone(),
+                    keyword_location: scope.keyword_location.clone(),
+                    absolute_keyword_location: Some(base.clone()),
+                    base,
+                    dialect: scope.dialect,
+                };
+                &rebased
+            }
+            None => scope,
+        };
+
+        self.evaluate_in(schema, instance, scope)
+    }
+
+    /// Evaluates a schema within a scope whose base URI already accounts for the schema's id.
+    fn evaluate_in(&mut self, schema: &'a Schema, instance: &Value, scope: &Scope) -> Evaluation {
+        let redeclared;
+        let scope = match schema.dialect() {
+            Some(dialect) if dialect != scope.dialect => {
+                redeclared = Scope {
+                    dialect,
+                    ..scope.clone()
+                };
+                &redeclared
+            }
+            _ => scope,
+        };
+
+        let is_resource =
+            ptr::eq(schema, self.root) || schema.get_id().is_some_and(|id| !id.starts_with('#'));
+        if is_resource {
+            self.dynamic_scope.enter(schema, scope.base.clone());
+        }
+
+        let mut evaluation = Evaluation::new(scope, self.record_passing);
+
+        self.reference(schema, instance, scope, &mut evaluation);
+
+        // Drafts before 2019-09 ignore the keywords next to a `$ref`.
+        if schema.reference.is_none() || scope.dialect > Dialect::Draft7 {
+            schema_type(schema, instance, scope, &mut evaluation);
+            enumeration(schema, instance, scope, &mut evaluation);
+            numeric(schema, instance, scope, &mut evaluation);
+            self.string(schema, instance, scope, &mut evaluation);
+            self.array(schema, instance, scope, &mut evaluation);
+            self.object(schema, instance, scope, &mut evaluation);
+            self.composition(schema, instance, scope, &mut evaluation);
+            self.conditional(schema, instance, scope, &mut evaluation);
+            self.unevaluated_items(schema, instance, scope, &mut evaluation);
+            self.unevaluated_properties(schema, instance, scope, &mut evaluation);
+            annotations(schema, scope, &mut evaluation);
+        }
+
+        if is_resource {
+            self.dynamic_scope.leave();
+        }
+
+        evaluation
+    }
+
+    fn evaluate_node(
+        &mut self,
+        node: &'a BooleanOrSchema,
+        instance: &Value,
+        scope: &Scope,
+    ) -> Evaluation {
+        match node {
+            BooleanOrSchema::Boolean(true) => Evaluation::new(scope, self.record_passing),
+            BooleanOrSchema::Boolean(false) => {
+                let mut evaluation = Evaluation::new(scope, self.record_passing);
+                evaluation.node = scope.node(Some(ValidationErrorKind::FalseSchema));
+                evaluation
+            }
+            BooleanOrSchema::InnerSchema(schema) => self.evaluate(schema, instance, scope),
+        }
+    }
+
+    fn regex(&mut self, pattern: &'a str) -> Option<&Regex> {
+        self.patterns
+            .entry(pattern)
+            .or_insert_with(|| Regex::new(pattern).ok())
+            .as_ref()
+    }
+
+    fn lookup(&self, uri: &str) -> Option<ResolvedSchema<'a>> {
+        let uri = uri.strip_suffix('#').unwrap_or(uri);
+
+        self.registry.resolve(&self.root_base, uri).or_else(|| {
+            // The schema being validated need not be registered.
+            let (document, fragment) = uri.split_once('#').unwrap_or((uri, ""));
+            let root = ResolvedSchema::new(self.root, self.root_base.clone());
+
+            (document == self.root_base)
+                .then_some(root)?
+                .fragment(fragment)
+        })
+    }
+
+    fn reference(
+        &mut self,
+        schema: &'a Schema,
+        instance: &Value,
+        scope: &Scope,
+        evaluation: &mut Evaluation,
+    ) {
+        if let Some(reference) = &schema.reference {
+            let uri = join_reference(&scope.base, reference);
+            let target = self.lookup(&uri);
+
+            self.follow("$ref", reference, target, instance, scope, evaluation);
+        }
+
+        if let Some(reference) = &schema.dynamic_reference {
+            let uri = join_reference(&scope.base, reference);
+            let target = self
+                .lookup(&uri)
+                .map(|target| self.dynamic_scope.resolve(reference, target));
+
+            self.follow(
+                "$dynamicRef",
+                reference,
+                target,
+                instance,
+                scope,
+                evaluation,
+            );
+        }
+
+        if let Some(reference) = &schema.recursive_reference {
+            let uri = join_reference(&scope.base, reference);
+            let target = self
+                .lookup(&uri)
+                .map(|target| self.dynamic_scope.resolve_recursive(target));
+
+            self.follow(
+                "$recursiveRef",
+                reference,
+                target,
+                instance,
+                scope,
+                evaluation,
+            );
+        }
+    }
+
+    fn follow(
+        &mut self,
+        keyword: &str,
+        reference: &str,
+        target: Option<ResolvedSchema<'a>>,
+        instance: &Value,
+        scope: &Scope,
+        evaluation: &mut Evaluation,
+    ) {
+        let Some(target) = target else {
+            evaluation.fail(
+                scope,
+                keyword,
+                ValidationErrorKind::UnresolvableReference(reference.to_owned()),
+            );
+            return;
+        };
+
+        let cyclic = self.references.iter().any(|(active, location)| {
+            ptr::eq(*active, target.schema()) && *location == scope.instance_location
+        });
+
+        if cyclic {
+            evaluation.fail(
+                scope,
+                keyword,
+                ValidationErrorKind::CyclicReference(reference.to_owned()),
+            );
+            return;
+        }
+
+        // The dynamic scope may have moved the target into another resource than the one the
+        // reference names, so the location is built from where it ended up. Its base URI already
+        // accounts for its own id.
+        let uri = join_reference(&scope.base, reference);
+        let scope = Scope {
+            absolute_keyword_location: Some(target_location(&uri, &target)),
+            base: target.base_uri().to_owned(),
+            ..scope.in_place(keyword)
+        };
+
+        self.references
+            .push((target.schema(), scope.instance_location.clone()));
+        let referenced = self.evaluate_in(target.schema(), instance, &scope);
+        self.references.pop();
+
+        evaluation.absorb(&referenced);
+
+        if !referenced.is_valid() || self.record_passing {
+            evaluation.push(referenced.node);
+        }
+    }
+
+    fn string(
+        &mut self,
+        schema: &'a Schema,
+        instance: &Value,
+        scope: &Scope,
+        evaluation: &mut Evaluation,
+    ) {
+        let Value::String(string) = instance else {
+            return;
+        };
+
+        let length = string.chars().count() as i64;
+
+        if let Some(min_length) = schema.min_length {
+            evaluation.assert(
+                scope,
+                "minLength",
+                (length < min_length).then_some(ValidationErrorKind::MinLength(min_length)),
+            );
+        }
+
+        if let Some(max_length) = schema.max_length {
+            evaluation.assert(
+                scope,
+                "maxLength",
+                (length > max_length).then_some(ValidationErrorKind::MaxLength(max_length)),
+            );
+        }
+
+        if let Some(pattern) = &schema.pattern {
+            let failure = match self.regex(pattern) {
+                Some(regex) if regex.is_match(string) => None,
+                Some(_) => Some(ValidationErrorKind::Pattern(pattern.to_owned())),
+                None => Some(ValidationErrorKind::InvalidPattern(pattern.to_owned())),
+            };
+
+            evaluation.assert(scope, "pattern", failure);
+        }
+    }
+
+    fn array(
+        &mut self,
+        schema: &'a Schema,
+        instance: &Value,
+        scope: &Scope,
+        evaluation: &mut Evaluation,
+    ) {
+        let Value::Array(items) = instance else {
+            return;
+        };
+
+        let prefix_items = schema.prefix_items.as_deref().unwrap_or_default();
+
+        if !prefix_items.is_empty() {
+            let mut children = Vec::new();
+
+            for (index, (prefix_schema, item)) in prefix_items.iter().zip(items).enumerate() {
+                let item_scope = scope.descend(&index.to_string(), &format!("prefixItems/{index}"));
+
+                children.push(self.evaluate_node(prefix_schema, item, &item_scope).node);
+                evaluation.evaluated_items.insert(index);
+            }
+
+            let annotation = match items.len() > prefix_items.len() {
+                true => Value::from(prefix_items.len() - 1),
+                false => Value::Bool(true),
+            };
+
+            evaluation.apply(scope, "prefixItems", children, None, Some(annotation));
+        }
+
+        match &schema.items {
+            Some(SchemaOrSchemaArray::Schema(items_schema)) => {
+                let mut children = Vec::new();
+
+                for (index, item) in items.iter().enumerate().skip(prefix_items.len()) {
+                    let item_scope = scope.descend(&index.to_string(), "items");
+
+                    children.push(self.evaluate_node(items_schema, item, &item_scope).node);
+                    evaluation.evaluated_items.insert(index);
+                }
+
+                evaluation.apply(scope, "items", children, None, Some(Value::Bool(true)));
+            }
+            // Draft 2019-09 and earlier match an array of schemas positionally and leave the
+            // remaining items to `additionalItems`.
+            Some(SchemaOrSchemaArray::Array(items_schemas)) => {
+                let mut children = Vec::new();
+
+                for (index, (item_schema, item)) in items_schemas.iter().zip(items).enumerate() {
+                    let item_scope = scope.descend(&index.to_string(), &format!("items/{index}"));
+
+                    children.push(self.evaluate_node(item_schema, item, &item_scope).node);
+                    evaluation.evaluated_items.insert(index);
+                }
+
+                evaluation.apply(scope, "items", children, None, None);
+
+                if let Some(additional_items) = &schema.additional_items {
+                    let mut children = Vec::new();
+
+                    for (index, item) in items.iter().enumerate().skip(items_schemas.len()) {
+                        let item_scope = scope.descend(&index.to_string(), "additionalItems");
+
+                        children.push(self.evaluate_node(additional_items, item, &item_scope).node);
+                        evaluation.evaluated_items.insert(index);
+                    }
+
+                    evaluation.apply(
+                        scope,
+                        "additionalItems",
+                        children,
+                        None,
+                        Some(Value::Bool(true)),
+                    );
+                }
+            }
+            None => {}
+        }
+
+        if let Some(contains) = &schema.contains {
+            let mut matched = Vec::new();
+            let mut unmatched = Vec::new();
+
+            for (index, item) in items.iter().enumerate() {
+                let item_scope = scope.descend(&index.to_string(), "contains");
+                let item_evaluation = self.evaluate_node(contains, item, &item_scope);
+
+                match item_evaluation.is_valid() {
+                    true => {
+                        matched.push(index);
+                        evaluation.evaluated_items.insert(index);
+                    }
+                    false => unmatched.push(item_evaluation.node),
+                }
+            }
+
+            let count = matched.len() as i64;
+
+            match (schema.min_contains, count) {
+                (None, 0) => evaluation.apply(
+                    scope,
+                    "contains",
+                    unmatched,
+                    Some(ValidationErrorKind::Contains),
+                    None,
+                ),
+                _ => evaluation.apply(scope, "contains", Vec::new(), None, Some(matched.into())),
+            }
+
+            if let Some(min_contains) = schema.min_contains {
+                evaluation.assert(
+                    scope,
+                    "minContains",
+                    (count < min_contains)
+                        .then_some(ValidationErrorKind::MinContains(min_contains)),
+                );
+            }
+
+            if let Some(max_contains) = schema.max_contains {
+                evaluation.assert(
+                    scope,
+                    "maxContains",
+                    (count > max_contains)
+                        .then_some(ValidationErrorKind::MaxContains(max_contains)),
+                );
+            }
+        }
+
+        if let Some(min_items) = schema.min_items {
+            evaluation.assert(
+                scope,
+                "minItems",
+                ((items.len() as i64) < min_items)
+                    .then_some(ValidationErrorKind::MinItems(min_items)),
+            );
+        }
+
+        if let Some(max_items) = schema.max_items {
+            evaluation.assert(
+                scope,
+                "maxItems",
+                ((items.len() as i64) > max_items)
+                    .then_some(ValidationErrorKind::MaxItems(max_items)),
+            );
+        }
+
+        if schema.unique_items == Some(true) {
+            let duplicate = items.iter().enumerate().any(|(index, item)| {
+                items[index + 1..]
+                    .iter()
+                    .any(|other| json_equals(item, other))
+            });
+
+            evaluation.assert(
+                scope,
+                "uniqueItems",
+                duplicate.then_some(ValidationErrorKind::UniqueItems),
+            );
+        }
+    }
+
+    fn object(
+        &mut self,
+        schema: &'a Schema,
+        instance: &Value,
+        scope: &Scope,
+        evaluation: &mut Evaluation,
+    ) {
+        let Value::Object(object) = instance else {
+            return;
+        };
+
+        let mut covered: HashSet<&str> = HashSet::new();
+
+        if let Some(properties) = &schema.properties {
+            let mut children = Vec::new();
+            let mut names = Vec::new();
+
+            for (name, property_schema) in properties {
+                if let Some(value) = object.get(name) {
+                    let property_scope =
+                        scope.descend(name, &format!("properties/{}", escape(name)));
+
+                    children.push(
+                        self.evaluate_node(property_schema, value, &property_scope)
+                            .node,
+                    );
+                    names.push(name.to_owned());
+                    covered.insert(name);
+                }
+            }
+
+            evaluation
+                .evaluated_properties
+                .extend(names.iter().cloned());
+            evaluation.apply(scope, "properties", children, None, Some(names.into()));
+        }
+
+        if let Some(pattern_properties) = &schema.pattern_properties {
+            let mut children = Vec::new();
+            let mut names = Vec::new();
+
+            for (pattern, pattern_schema) in pattern_properties {
+                let pattern_path = format!("patternProperties/{}", escape(pattern));
+
+                let Some(regex) = self.regex(pattern).cloned() else {
+                    evaluation.fail(
+                        scope,
+                        &pattern_path,
+                        ValidationErrorKind::InvalidPattern(pattern.to_owned()),
+                    );
+                    continue;
+                };
+
+                for (name, value) in object.iter().filter(|(name, _)| regex.is_match(name)) {
+                    let property_scope = scope.descend(name, &pattern_path);
+
+                    children.push(
+                        self.evaluate_node(pattern_schema, value, &property_scope)
+                            .node,
+                    );
+                    names.push(name.to_owned());
+                    covered.insert(name);
+                }
+            }
+
+            evaluation
+                .evaluated_properties
+                .extend(names.iter().cloned());
+            evaluation.apply(
+                scope,
+                "patternProperties",
+                children,
+                None,
+                Some(names.into()),
+            );
+        }
+
+        if let Some(additional_properties) = &schema.additional_properties {
+            let mut children = Vec::new();
+            let mut names = Vec::new();
+
+            for (name, value) in object
+                .iter()
+                .filter(|(name, _)| !covered.contains(name.as_str()))
+            {
+                let property_scope = scope.descend(name, "additionalProperties");
+
+                children.push(
+                    self.evaluate_node(additional_properties, value, &property_scope)
+                        .node,
+                );
+                names.push(name.to_owned());
+            }
+
+            evaluation
+                .evaluated_properties
+                .extend(names.iter().cloned());
+            evaluation.apply(
+                scope,
+                "additionalProperties",
+                children,
+                None,
+                Some(names.into()),
+            );
+        }
+
+        if let Some(property_names) = &schema.property_names {
+            let mut children = Vec::new();
+            let names_scope = scope.in_place("propertyNames");
+
+            for name in object.keys() {
+                let name_instance = Value::String(name.to_owned());
+                let name_evaluation =
+                    self.evaluate_node(property_names, &name_instance, &names_scope);
+
+                match name_evaluation.is_valid() {
+                    true => children.push(name_evaluation.node),
+                    false => evaluation.apply(
+                        scope,
+                        "propertyNames",
+                        vec![name_evaluation.node],
+                        Some(ValidationErrorKind::PropertyNames(name.to_owned())),
+                        None,
+                    ),
+                }
+            }
+
+            if children.len() == object.len() {
+                evaluation.apply(scope, "propertyNames", children, None, None);
+            }
+        }
+
+        if let Some(required) = &schema.required {
+            let missing = required
+                .iter()
+                .filter(|property| !object.contains_key(*property))
+                .map(|property| ValidationErrorKind::Required(property.to_owned()))
+                .collect::<Vec<ValidationErrorKind>>();
+
+            match missing.is_empty() {
+                true => evaluation.assert(scope, "required", None),
+                false => missing
+                    .into_iter()
+                    .for_each(|kind| evaluation.fail(scope, "required", kind)),
+            }
+        }
+
+        if let Some(min_properties) = schema.min_properties {
+            evaluation.assert(
+                scope,
+                "minProperties",
+                ((object.len() as i64) < min_properties)
+                    .then_some(ValidationErrorKind::MinProperties(min_properties)),
+            );
+        }
+
+        if let Some(max_properties) = schema.max_properties {
+            evaluation.assert(
+                scope,
+                "maxProperties",
+                ((object.len() as i64) > max_properties)
+                    .then_some(ValidationErrorKind::MaxProperties(max_properties)),
+            );
+        }
+
+        for (property, dependencies) in schema.dependent_required.iter().flatten() {
+            let keyword = format!("dependentRequired/{}", escape(property));
+            dependent_required(object, property, dependencies, scope, &keyword, evaluation);
+        }
+
+        // Before 2019-09 `dependencies` holds both the required properties and the schemas.
+        for (property, dependency) in schema.dependencies.iter().flatten() {
+            if let SchemaOrStringArray::Array(dependencies) = dependency {
+                let keyword = format!("dependencies/{}", escape(property));
+                dependent_required(object, property, dependencies, scope, &keyword, evaluation);
+            }
+        }
+
+        if let Some(dependent_schemas) = &schema.dependent_schemas {
+            let mut children = Vec::new();
+
+            for (property, dependent_schema) in dependent_schemas {
+                if object.contains_key(property) {
+                    let dependent_scope =
+                        scope.in_place(&format!("dependentSchemas/{}", escape(property)));
+                    let dependent_evaluation =
+                        self.evaluate_node(dependent_schema, instance, &dependent_scope);
+
+                    evaluation.absorb(&dependent_evaluation);
+                    children.push(dependent_evaluation.node);
+                }
+            }
+
+            evaluation.apply(scope, "dependentSchemas", children, None, None);
+        }
+
+        let dependent_schemas = schema
+            .dependencies
+            .iter()
+            .flatten()
+            .filter_map(|(property, dependency)| match dependency {
+                SchemaOrStringArray::Schema(dependent_schema) => Some((property, dependent_schema)),
+                SchemaOrStringArray::Array(_) => None,
+            })
+            .collect::<Vec<(&String, &BooleanOrSchema)>>();
+
+        if !dependent_schemas.is_empty() {
+            let mut children = Vec::new();
+
+            for (property, dependent_schema) in dependent_schemas {
+                if object.contains_key(property) {
+                    let dependent_scope =
+                        scope.in_place(&format!("dependencies/{}", escape(property)));
+                    let dependent_evaluation =
+                        self.evaluate_node(dependent_schema, instance, &dependent_scope);
+
+                    evaluation.absorb(&dependent_evaluation);
+                    children.push(dependent_evaluation.node);
+                }
+            }
+
+            evaluation.apply(scope, "dependencies", children, None, None);
+        }
+    }
+
+    fn composition(
+        &mut self,
+        schema: &'a Schema,
+        instance: &Value,
+        scope: &Scope,
+        evaluation: &mut Evaluation,
+    ) {
+        if let Some(all_of) = &schema.all_of {
+            let mut children = Vec::new();
+
+            for (index, subschema) in all_of.iter().enumerate() {
+                let subschema_evaluation = self.evaluate_node(
+                    subschema,
+                    instance,
+                    &scope.in_place(&format!("allOf/{index}")),
+                );
+
+                evaluation.absorb(&subschema_evaluation);
+                children.push(subschema_evaluation.node);
+            }
+
+            evaluation.apply(scope, "allOf", children, None, None);
+        }
+
+        if let Some(any_of) = &schema.any_of {
+            let mut matched = Vec::new();
+            let mut unmatched = Vec::new();
+
+            for (index, subschema) in any_of.iter().enumerate() {
+                let subschema_evaluation = self.evaluate_node(
+                    subschema,
+                    instance,
+                    &scope.in_place(&format!("anyOf/{index}")),
+                );
+
+                evaluation.absorb(&subschema_evaluation);
+
+                match subschema_evaluation.is_valid() {
+                    true => matched.push(subschema_evaluation.node),
+                    false => unmatched.push(subschema_evaluation.node),
+                }
+            }
+
+            match matched.is_empty() {
+                true => evaluation.apply(
+                    scope,
+                    "anyOf",
+                    unmatched,
+                    Some(ValidationErrorKind::AnyOf),
+                    None,
+                ),
+                false => evaluation.apply(scope, "anyOf", matched, None, None),
+            }
+        }
+
+        if let Some(one_of) = &schema.one_of {
+            let mut matched = Vec::new();
+            let mut children = Vec::new();
+
+            for (index, subschema) in one_of.iter().enumerate() {
+                let subschema_evaluation = self.evaluate_node(
+                    subschema,
+                    instance,
+                    &scope.in_place(&format!("oneOf/{index}")),
+                );
+
+                if subschema_evaluation.is_valid() {
+                    matched.push(index);
+                }
+
+                children.push(subschema_evaluation);
+            }
+
+            match matched.len() {
+                1 => {
+                    let matched = children.swap_remove(matched[0]);
+
+                    evaluation.absorb(&matched);
+                    evaluation.apply(scope, "oneOf", vec![matched.node], None, None);
+                }
+                count => evaluation.apply(
+                    scope,
+                    "oneOf",
+                    children
+                        .into_iter()
+                        .map(|evaluation| evaluation.node)
+                        .collect(),
+                    Some(ValidationErrorKind::OneOf(count)),
+                    None,
+                ),
+            }
+        }
+
+        if let Some(not) = &schema.not {
+            let not_evaluation = self.evaluate_node(not, instance, &scope.in_place("not"));
+
+            evaluation.assert(
+                scope,
+                "not",
+                not_evaluation
+                    .is_valid()
+                    .then_some(ValidationErrorKind::Not),
+            );
+        }
+    }
+
+    fn conditional(
+        &mut self,
+        schema: &'a Schema,
+        instance: &Value,
+        scope: &Scope,
+        evaluation: &mut Evaluation,
+    ) {
+        let Some(schema_if) = &schema.schema_if else {
+            return;
+        };
+
+        let if_evaluation = self.evaluate_node(schema_if, instance, &scope.in_place("if"));
+        let (keyword, branch) = match if_evaluation.is_valid() {
+            true => ("then", &schema.schema_then),
+            false => ("else", &schema.schema_else),
+        };
+
+        evaluation.absorb(&if_evaluation);
+
+        // Whether `if` passes only picks the branch, so it never fails its parent.
+        if self.record_passing {
+            let mut node = if_evaluation.node;
+            node.annotation_only = true;
+            evaluation.node.children.push(node);
+        }
+
+        if let Some(branch) = branch {
+            let branch_evaluation = self.evaluate_node(branch, instance, &scope.in_place(keyword));
+
+            evaluation.absorb(&branch_evaluation);
+            evaluation.apply(scope, keyword, vec![branch_evaluation.node], None, None);
+        }
+    }
+
+    fn unevaluated_items(
+        &mut self,
+        schema: &'a Schema,
+        instance: &Value,
+        scope: &Scope,
+        evaluation: &mut Evaluation,
+    ) {
+        let (Some(unevaluated_items), Value::Array(items)) = (&schema.unevaluated_items, instance)
+        else {
+            return;
+        };
+
+        let mut children = Vec::new();
+
+        for (index, item) in items.iter().enumerate() {
+            if evaluation.evaluated_items.contains(&index) {
+                continue;
+            }
+
+            let item_scope = scope.descend(&index.to_string(), "unevaluatedItems");
+            children.push(
+                self.evaluate_node(unevaluated_items, item, &item_scope)
+                    .node,
+            );
+        }
+
+        let annotation = (!children.is_empty()).then_some(Value::Bool(true));
+
+        evaluation.evaluated_items.extend(0..items.len());
+        evaluation.apply(scope, "unevaluatedItems", children, None, annotation);
+    }
+
+    fn unevaluated_properties(
+        &mut self,
+        schema: &'a Schema,
+        instance: &Value,
+        scope: &Scope,
+        evaluation: &mut Evaluation,
+    ) {
+        let (Some(unevaluated_properties), Value::Object(object)) =
+            (&schema.unevaluated_properties, instance)
+        else {
+            return;
+        };
+
+        let mut children = Vec::new();
+        let mut names = Vec::new();
+
+        for (name, value) in object {
+            if evaluation.evaluated_properties.contains(name) {
+                continue;
+            }
+
+            let property_scope = scope.descend(name, "unevaluatedProperties");
+
+            children.push(
+                self.evaluate_node(unevaluated_properties, value, &property_scope)
+                    .node,
+            );
+            names.push(name.to_owned());
+        }
+
+        evaluation
+            .evaluated_properties
+            .extend(names.iter().cloned());
+        evaluation.apply(
+            scope,
+            "unevaluatedProperties",
+            children,
+            None,
+            Some(names.into()),
+        );
+    }
+}
+
+/// Fails for every dependency missing from the object when the property is present.
+fn dependent_required(
+    object: &serde_json::Map<String, Value>,
+    property: &str,
+    dependencies: &[String],
+    scope: &Scope,
+    keyword: &str,
+    evaluation: &mut Evaluation,
+) {
+    if !object.contains_key(property) {
+        return;
+    }
+
+    let missing = dependencies
+        .iter()
+        .filter(|dependency| !object.contains_key(*dependency))
+        .map(|dependency| {
+            ValidationErrorKind::DependentRequired(property.to_owned(), dependency.to_owned())
+        })
+        .collect::<Vec<ValidationErrorKind>>();
+
+    match missing.is_empty() {
+        true => evaluation.assert(scope, keyword, None),
+        false => missing
+            .into_iter()
+            .for_each(|kind| evaluation.fail(scope, keyword, kind)),
+    }
+}
+
+fn schema_type(schema: &Schema, instance: &Value, scope: &Scope, evaluation: &mut Evaluation) {
+    let expected = match &schema.schema_type {
+        Some(StringOrStringArray::String(json_type)) => vec![json_type.to_owned()],
+        Some(StringOrStringArray::Array(json_types)) => json_types.to_owned(),
+        None => return,
+    };
+
+    let matches = expected
+        .iter()
+        .any(|json_type| is_of_type(instance, json_type));
+
+    evaluation.assert(
+        scope,
+        "type",
+        (!matches).then_some(ValidationErrorKind::Type(expected)),
+    );
+}
+
+fn enumeration(schema: &Schema, instance: &Value, scope: &Scope, evaluation: &mut Evaluation) {
+    if let Some(schema_enum) = &schema.schema_enum {
+        let matches = schema_enum
+            .iter()
+            .any(|option| any_type_equals(option, instance));
+
+        evaluation.assert(
+            scope,
+            "enum",
+            (!matches).then_some(ValidationErrorKind::Enum),
+        );
+    }
+
+    if let Some(schema_const) = &schema.schema_const {
+        let matches = any_type_equals(schema_const, instance);
+
+        evaluation.assert(
+            scope,
+            "const",
+            (!matches).then_some(ValidationErrorKind::Const),
+        );
+    }
+}
+
+fn numeric(schema: &Schema, instance: &Value, scope: &Scope, evaluation: &mut Evaluation) {
+    let Value::Number(number) = instance else {
+        return;
+    };
+
+    // Draft 4 spells exclusive limits as boolean modifiers of `minimum` and `maximum`.
+    let exclusive = matches!(
+        schema.exclusive_minimum,
+        Some(BooleanOrNumber::Boolean(true))
+    );
+
+    if let Some(minimum) = schema.minimum.as_ref().map(Limit::from) {
+        match compare(number, minimum) {
+            Some(Ordering::Less) => evaluation.fail(
+                scope,
+                "minimum",
+                ValidationErrorKind::Minimum(minimum.into()),
+            ),
+            Some(Ordering::Equal) if exclusive => evaluation.fail(
+                scope,
+                "exclusiveMinimum",
+                ValidationErrorKind::ExclusiveMinimum(minimum.into()),
+            ),
+            _ => evaluation.assert(scope, "minimum", None),
+        }
+    }
+
+    if let Some(BooleanOrNumber::Number(exclusive_minimum)) = &schema.exclusive_minimum {
+        let exclusive_minimum = Limit::from(exclusive_minimum);
+        let reached = compare(number, exclusive_minimum) != Some(Ordering::Greater);
+
+        evaluation.assert(
+            scope,
+            "exclusiveMinimum",
+            reached.then(|| ValidationErrorKind::ExclusiveMinimum(exclusive_minimum.into())),
+        );
+    }
+
+    let exclusive = matches!(
+        schema.exlusive_maximum,
+        Some(BooleanOrNumber::Boolean(true))
+    );
+
+    if let Some(maximum) = schema.maximum.as_ref().map(Limit::from) {
+        match compare(number, maximum) {
+            Some(Ordering::Greater) => evaluation.fail(
+                scope,
+                "maximum",
+                ValidationErrorKind::Maximum(maximum.into()),
+            ),
+            Some(Ordering::Equal) if exclusive => evaluation.fail(
+                scope,
+                "exclusiveMaximum",
+                ValidationErrorKind::ExclusiveMaximum(maximum.into()),
+            ),
+            _ => evaluation.assert(scope, "maximum", None),
+        }
+    }
+
+    let exclusive_maximum = match &schema.exlusive_maximum {
+        Some(BooleanOrNumber::Number(value)) => Some(Limit::from(value)),
+        _ => None,
+    };
+
+    if let Some(exclusive_maximum) = exclusive_maximum {
+        let reached = compare(number, exclusive_maximum) != Some(Ordering::Less);
+
+        evaluation.assert(
+            scope,
+            "exclusiveMaximum",
+            reached.then(|| ValidationErrorKind::ExclusiveMaximum(exclusive_maximum.into())),
+        );
+    }
+
+    if let Some(multiple_of) = schema.multiple_of.as_ref().map(Limit::from) {
+        let multiple = is_multiple_of(number, multiple_of);
+
+        evaluation.assert(
+            scope,
+            "multipleOf",
+            (!multiple).then(|| ValidationErrorKind::MultipleOf(multiple_of.into())),
+        );
+    }
+}
+
+fn annotations(schema: &Schema, scope: &Scope, evaluation: &mut Evaluation) {
+    if !evaluation.record_passing || !evaluation.is_valid() {
+        return;
+    }
+
+    let strings = [
+        ("title", &schema.title),
+        ("description", &schema.description),
+        ("format", &schema.format),
+        ("contentEncoding", &schema.content_encoding),
+        ("contentMediaType", &schema.content_media_type),
+    ];
+
+    for (keyword, value) in strings {
+        if let Some(value) = value {
+            evaluation.annotate(scope, keyword, Some(Value::from(value.to_owned())));
+        }
+    }
+
+    let booleans = [
+        ("deprecated", schema.deprecated),
+        ("readOnly", schema.read_only),
+        ("writeOnly", schema.write_only),
+    ];
+
+    for (keyword, value) in booleans {
+        if let Some(value) = value {
+            evaluation.annotate(scope, keyword, Some(Value::from(value)));
+        }
+    }
+
+    if let Some(default) = &schema.default {
+        evaluation.annotate(scope, "default", serde_json::to_value(default).ok());
+    }
+
+    if let Some(examples) = &schema.examples {
+        evaluation.annotate(scope, "examples", serde_json::to_value(examples).ok());
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    instance_location: String,
+    keyword_location: String,
+    absolute_keyword_location: Option<String>,
+    kind: ValidationErrorKind,
+}
+
+impl ValidationError {
+    pub fn instance_location(&self) -> &str {
+        &self.instance_location
+    }
+
+    pub fn keyword_location(&self) -> &str {
+        &self.keyword_location
+    }
+
+    pub fn absolute_keyword_location(&self) -> Option<&str> {
+        self.absolute_keyword_location.as_deref()
+    }
+
+    pub fn kind(&self) -> &ValidationErrorKind {
+        &self.kind
+    }
+}
+
+impl Error for ValidationError {}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} (instance location '{}', keyword location '{}')",
+            self.kind, self.instance_location, self.keyword_location
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationErrorKind {
+    FalseSchema,
+    Type(Vec<String>),
+    Enum,
+    Const,
+    Minimum(Number),
+    ExclusiveMinimum(Number),
+    Maximum(Number),
+    ExclusiveMaximum(Number),
+    MultipleOf(Number),
+    MinLength(i64),
+    MaxLength(i64),
+    Pattern(String),
+    InvalidPattern(String),
+    MinItems(i64),
+    MaxItems(i64),
+    UniqueItems,
+    Contains,
+    MinContains(i64),
+    MaxContains(i64),
+    MinProperties(i64),
+    MaxProperties(i64),
+    Required(String),
+    DependentRequired(String, String),
+    PropertyNames(String),
+    AnyOf,
+    OneOf(usize),
+    Not,
+    UnresolvableReference(String),
+    CyclicReference(String),
+}
+
+impl Display for ValidationErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FalseSchema => write!(f, "No value is valid against the false schema"),
+            Self::Type(types) => write!(f, "Value is not of type {}", types.join(" or ")),
+            Self::Enum => write!(f, "Value is not one of the enumerated values"),
+            Self::Const => write!(f, "Value is not equal to the constant"),
+            Self::Minimum(limit) => write!(f, "Value is less than the minimum of {}", limit),
+            Self::ExclusiveMinimum(limit) => {
+                write!(
+                    f,
+                    "Value is not greater than the exclusive minimum of {}",
+                    limit
+                )
+            }
+            Self::Maximum(limit) => write!(f, "Value is greater than the maximum of {}", limit),
+            Self::ExclusiveMaximum(limit) => {
+                write!(
+                    f,
+                    "Value is not less than the exclusive maximum of {}",
+                    limit
+                )
+            }
+            Self::MultipleOf(divisor) => write!(f, "Value is not a multiple of {}", divisor),
+            Self::MinLength(limit) => write!(f, "String is shorter than {} characters", limit),
+            Self::MaxLength(limit) => write!(f, "String is longer than {} characters", limit),
+            Self::Pattern(pattern) => write!(f, "String does not match pattern {}", pattern),
+            Self::InvalidPattern(pattern) => {
+                write!(f, "Pattern {} is not a valid regular expression", pattern)
+            }
+            Self::MinItems(limit) => write!(f, "Array has fewer than {} items", limit),
+            Self::MaxItems(limit) => write!(f, "Array has more than {} items", limit),
+            Self::UniqueItems => write!(f, "Array items are not unique"),
+            Self::Contains => write!(f, "Array does not contain a matching item"),
+            Self::MinContains(limit) => {
+                write!(f, "Array contains fewer than {} matching items", limit)
+            }
+            Self::MaxContains(limit) => {
+                write!(f, "Array contains more than {} matching items", limit)
+            }
+            Self::MinProperties(limit) => write!(f, "Object has fewer than {} properties", limit),
+            Self::MaxProperties(limit) => write!(f, "Object has more than {} properties", limit),
+            Self::Required(property) => write!(f, "Required property {} is missing", property),
+            Self::DependentRequired(property, dependency) => write!(
+                f,
+                "Property {} is required when {} is present",
+                dependency, property
+            ),
+            Self::PropertyNames(property) => {
+                write!(f, "Property name {} does not match the schema", property)
+            }
+            Self::AnyOf => write!(f, "Value does not match any of the subschemas"),
+            Self::OneOf(count) => write!(
+                f,
+                "Value matches {} subschemas instead of exactly one",
+                count
+            ),
+            Self::Not => write!(f, "Value matches a subschema it must not match"),
+            Self::UnresolvableReference(reference) => {
+                write!(f, "Reference {} is not resolvable", reference)
+            }
+            Self::CyclicReference(reference) => {
+                write!(
+                    f,
+                    "Reference {} loops without consuming the instance",
+                    reference
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use serde_json_schema::Dialect;
+
+    use super::*;
+
+    fn kinds(
+        schema: &Schema,
+        registry: &SchemaRegistry,
+        instance: Value,
+    ) -> Vec<ValidationErrorKind> {
+        Validator::new(schema, registry)
+            .validate(&instance)
+            .err()
+            .unwrap_or_default()
+            .into_iter()
+            .map(|error| error.kind)
+            .collect()
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/type#type-specific-keywords
+    #[test]
+    fn type_example() {
+        let schema: Schema = serde_json::from_str(r#"{ "type": ["number", "string"] }"#).unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+
+        assert!(validator.is_valid(&json!(42)));
+        assert!(validator.is_valid(&json!("Life, the universe, and everything")));
+        assert!(!validator.is_valid(&json!(["Life", "the universe", "and everything"])));
+
+        let integer: Schema = serde_json::from_str(r#"{ "type": "integer" }"#).unwrap();
+        let validator = Validator::new(&integer, &registry);
+
+        assert!(validator.is_valid(&json!(1.0)));
+        assert!(!validator.is_valid(&json!(2.5)));
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/enum
+    #[test]
+    fn enumerated_values_example() {
+        let schema: Schema =
+            serde_json::from_str(r#"{ "enum": ["red", "amber", "green", null, 42, [1, 2]] }"#)
+                .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+
+        assert!(validator.is_valid(&json!("red")));
+        assert!(validator.is_valid(&json!(null)));
+        assert!(validator.is_valid(&json!(42.0)));
+        assert!(validator.is_valid(&json!([1, 2])));
+        assert_eq!(
+            kinds(&schema, &registry, json!(0)),
+            vec![ValidationErrorKind::Enum]
+        );
+
+        let schema: Schema =
+            serde_json::from_str(r#"{ "const": "United States of America" }"#).unwrap();
+        let validator = Validator::new(&schema, &registry);
+
+        assert!(validator.is_valid(&json!("United States of America")));
+        assert!(!validator.is_valid(&json!("Canada")));
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/numeric#range
+    #[test]
+    fn numeric_range_example() {
+        let schema: Schema = serde_json::from_str(
+            r#"{ "type": "number", "minimum": 0, "exclusiveMaximum": 100, "multipleOf": 0.5 }"#,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+
+        assert!(validator.is_valid(&json!(0)));
+        assert!(validator.is_valid(&json!(99.5)));
+        assert_eq!(
+            kinds(&schema, &registry, json!(-1)),
+            vec![ValidationErrorKind::Minimum(Number::from(0))]
+        );
+        assert_eq!(
+            kinds(&schema, &registry, json!(100)),
+            vec![ValidationErrorKind::ExclusiveMaximum(Number::from(100))]
+        );
+        assert!(!validator.is_valid(&json!(10.25)));
+
+        let draft_4: Schema =
+            serde_json::from_str(r#"{ "maximum": 100, "exclusiveMaximum": true }"#).unwrap();
+        let validator = Validator::new(&draft_4, &registry);
+
+        assert!(validator.is_valid(&json!(99)));
+        assert!(!validator.is_valid(&json!(100)));
+
+        let latitude: Schema =
+            serde_json::from_str(r#"{ "minimum": -90.5, "maximum": 90.5 }"#).unwrap();
+        let validator = Validator::new(&latitude, &registry);
+
+        assert!(validator.is_valid(&json!(-90.5)));
+        assert_eq!(
+            kinds(&latitude, &registry, json!(-91)),
+            vec![ValidationErrorKind::Minimum(
+                Number::from_f64(-90.5).unwrap()
+            )]
+        );
+
+        let positive: Schema = serde_json::from_str(r#"{ "exclusiveMinimum": 0 }"#).unwrap();
+        let validator = Validator::new(&positive, &registry);
+
+        assert!(validator.is_valid(&json!(0.5)));
+        assert_eq!(
+            kinds(&positive, &registry, json!(0)),
+            vec![ValidationErrorKind::ExclusiveMinimum(Number::from(0))]
+        );
+
+        let draft_4: Schema =
+            serde_json::from_str(r#"{ "minimum": 0, "exclusiveMinimum": true }"#).unwrap();
+        let validator = Validator::new(&draft_4, &registry);
+
+        assert!(validator.is_valid(&json!(1)));
+        assert!(!validator.is_valid(&json!(0)));
+
+        let negative: Schema = serde_json::from_str(r#"{ "multipleOf": -1 }"#).unwrap();
+        let compiled = CompiledValidator::compile(&negative, &registry).unwrap();
+
+        assert!(Validator::new(&negative, &registry).is_valid(&json!(i64::MIN)));
+        assert!(compiled.is_valid(&json!(i64::MIN)));
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/string#length
+    #[test]
+    fn string_example() {
+        let schema: Schema = serde_json::from_str(
+            r#"{ "type": "string", "minLength": 2, "maxLength": 3, "pattern": "^[a-zé]+$" }"#,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+
+        assert!(validator.is_valid(&json!("éé")));
+        assert_eq!(
+            kinds(&schema, &registry, json!("a")),
+            vec![ValidationErrorKind::MinLength(2)]
+        );
+        assert_eq!(
+            kinds(&schema, &registry, json!("ABCD")),
+            vec![
+                ValidationErrorKind::MaxLength(3),
+                ValidationErrorKind::Pattern("^[a-zé]+$".to_owned())
+            ]
+        );
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/array#additionalitems
+    #[test]
+    fn tuple_validation_example() {
+        let schema: Schema = serde_json::from_str(
+            r#"{
+                "type": "array",
+                "prefixItems": [
+                    { "type": "number" },
+                    { "type": "string" },
+                    { "enum": ["Street", "Avenue", "Boulevard"] },
+                    { "enum": ["NW", "NE", "SW", "SE"] }
+                ],
+                "items": false
+            }"#,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+
+        assert!(validator.is_valid(&json!([1600, "Pennsylvania", "Avenue", "NW"])));
+        assert!(validator.is_valid(&json!([1600, "Pennsylvania", "Avenue"])));
+
+        let errors = validator
+            .validate(&json!([1600, "Pennsylvania", "Avenue", "NW", "Washington"]))
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].instance_location(), "/4");
+        assert_eq!(errors[0].keyword_location(), "/items");
+        assert_eq!(errors[0].kind(), &ValidationErrorKind::FalseSchema);
+
+        let draft_2019_09: Schema = serde_json::from_str(
+            r#"{
+                "type": "array",
+                "items": [
+                    { "type": "number" },
+                    { "type": "string" }
+                ],
+                "additionalItems": false
+            }"#,
+        )
+        .unwrap();
+        let validator = Validator::new(&draft_2019_09, &registry);
+
+        assert!(validator.is_valid(&json!([1600, "Pennsylvania"])));
+
+        let errors = validator
+            .validate(&json!(["1600", "Pennsylvania", "Avenue"]))
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].keyword_location(), "/items/0/type");
+        assert_eq!(errors[1].instance_location(), "/2");
+        assert_eq!(errors[1].keyword_location(), "/additionalItems");
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/array#mincontains-maxcontains
+    #[test]
+    fn array_example() {
+        let schema: Schema = serde_json::from_str(
+            r#"{
+                "type": "array",
+                "contains": { "type": "number" },
+                "minContains": 2,
+                "maxContains": 3,
+                "uniqueItems": true
+            }"#,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+
+        assert!(validator.is_valid(&json!(["apple", "orange", 2, 4])));
+        assert_eq!(
+            kinds(&schema, &registry, json!(["apple", "orange", 2])),
+            vec![ValidationErrorKind::MinContains(2)]
+        );
+        assert_eq!(
+            kinds(&schema, &registry, json!([1, 2, 3, 4])),
+            vec![ValidationErrorKind::MaxContains(3)]
+        );
+        assert_eq!(
+            kinds(&schema, &registry, json!([1, 2, 1.0])),
+            vec![ValidationErrorKind::UniqueItems]
+        );
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/object#additionalproperties
+    #[test]
+    fn object_example() {
+        let schema: Schema = serde_json::from_str(
+            r#"{
+                "type": "object",
+                "properties": {
+                    "builtin": { "type": "number" }
+                },
+                "patternProperties": {
+                    "^S_": { "type": "string" },
+                    "^I_": { "type": "integer" }
+                },
+                "additionalProperties": { "type": "string" },
+                "propertyNames": { "pattern": "^[A-Za-z_]+$" },
+                "required": ["builtin"],
+                "maxProperties": 3
+            }"#,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+
+        assert!(validator.is_valid(&json!({ "builtin": 42, "S_a": "b", "keyword": "value" })));
+
+        let errors = validator
+            .validate(&json!({ "builtin": 42, "I_0": 42.5 }))
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].instance_location(), "/I_0");
+        assert_eq!(errors[0].keyword_location(), "/patternProperties/^I_/type");
+        assert_eq!(
+            errors[1].kind(),
+            &ValidationErrorKind::PropertyNames("I_0".to_owned())
+        );
+
+        assert_eq!(
+            kinds(&schema, &registry, json!({ "keyword": 42 })),
+            vec![
+                ValidationErrorKind::Type(vec!["string".to_owned()]),
+                ValidationErrorKind::Required("builtin".to_owned())
+            ]
+        );
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/object#unevaluatedproperties
+    #[test]
+    fn unevaluated_properties_example() {
+        let schema: Schema = serde_json::from_str(
+            r#"{
+                "allOf": [
+                    {
+                        "type": "object",
+                        "properties": {
+                            "street_address": { "type": "string" },
+                            "city": { "type": "string" },
+                            "state": { "type": "string" }
+                        },
+                        "required": ["street_address", "city", "state"]
+                    }
+                ],
+                "properties": {
+                    "type": { "enum": ["residential", "business"] }
+                },
+                "required": ["type"],
+                "unevaluatedProperties": false
+            }"#,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+
+        assert!(validator.is_valid(&json!({
+            "street_address": "1600 Pennsylvania Avenue NW",
+            "city": "Washington",
+            "state": "DC",
+            "type": "business"
+        })));
+
+        let errors = validator
+            .validate(&json!({
+                "street_address": "1600 Pennsylvania Avenue NW",
+                "city": "Washington",
+                "state": "DC",
+                "type": "business",
+                "something that doesn't belong": "hi!"
+            }))
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].instance_location(),
+            "/something that doesn't belong"
+        );
+        assert_eq!(errors[0].keyword_location(), "/unevaluatedProperties");
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/combining
+    #[test]
+    fn composition_example() {
+        let schema: Schema = serde_json::from_str(
+            r#"{
+                "anyOf": [
+                    { "type": "string", "maxLength": 5 },
+                    { "type": "number", "minimum": 0 }
+                ],
+                "oneOf": [
+                    { "type": "number", "multipleOf": 5 },
+                    { "type": "number", "multipleOf": 3 },
+                    { "type": "string" }
+                ],
+                "not": { "const": "hello" }
+            }"#,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+
+        assert!(validator.is_valid(&json!("short")));
+        assert!(validator.is_valid(&json!(10)));
+        assert_eq!(
+            kinds(&schema, &registry, json!("too long")),
+            vec![ValidationErrorKind::AnyOf]
+        );
+        assert_eq!(
+            kinds(&schema, &registry, json!(15)),
+            vec![ValidationErrorKind::OneOf(2)]
+        );
+        assert_eq!(
+            kinds(&schema, &registry, json!("hello")),
+            vec![ValidationErrorKind::Not]
+        );
+    }
+
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-boolean-json-schemas
+    #[test]
+    fn boolean_subschemas_example() {
+        let schema: Schema = serde_json::from_str(
+            r#"{
+                "properties": {
+                    "name": true,
+                    "password": false
+                },
+                "allOf": [true],
+                "anyOf": [false, { "required": ["name"] }]
+            }"#,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+        let compiled = CompiledValidator::compile(&schema, &registry).unwrap();
+
+        assert!(validator.is_valid(&json!({ "name": "Ford Prefect" })));
+        assert!(compiled.is_valid(&json!({ "name": "Ford Prefect" })));
+        assert!(!compiled.is_valid(&json!({ "name": "Ford Prefect", "password": "42" })));
+
+        let errors = validator
+            .validate(&json!({ "name": "Ford Prefect", "password": "42" }))
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].instance_location(), "/password");
+        assert_eq!(errors[0].keyword_location(), "/properties/password");
+        assert_eq!(errors[0].kind(), &ValidationErrorKind::FalseSchema);
+    }
+
+    /// https://json-schema.org/learn/miscellaneous-examples#conditional-validation-with-if-else
+    #[test]
+    fn conditional_validation_with_if_else_example() {
+        let schema: Schema = serde_json::from_str(
+            r#"{
+                "type": "object",
+                "properties": {
+                    "isMember": { "type": "boolean" },
+                    "membershipNumber": { "type": "string" }
+                },
+                "required": ["isMember"],
+                "if": {
+                    "properties": { "isMember": { "const": true } }
+                },
+                "then": {
+                    "properties": {
+                        "membershipNumber": { "type": "string", "minLength": 10, "maxLength": 10 }
+                    }
+                },
+                "else": {
+                    "properties": {
+                        "membershipNumber": { "type": "string", "minLength": 15 }
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+
+        assert!(validator.is_valid(&json!({ "isMember": true, "membershipNumber": "1234567890" })));
+        assert!(validator
+            .is_valid(&json!({ "isMember": false, "membershipNumber": "GUEST1234567890" })));
+
+        let errors = validator
+            .validate(&json!({ "isMember": true, "membershipNumber": "GUEST1234567890" }))
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].keyword_location(),
+            "/then/properties/membershipNumber/maxLength"
+        );
+
+        // A failing `if` only picks `else`, so none of its own failures are errors.
+        let schema: Schema = serde_json::from_str(
+            r#"{
+                "if": { "required": ["a"] },
+                "then": { "required": ["c"] },
+                "else": { "required": ["b"] }
+            }"#,
+        )
+        .unwrap();
+        let validator = Validator::new(&schema, &registry);
+
+        for format in [OutputFormat::Basic, OutputFormat::Detailed] {
+            let output = serde_json::to_value(validator.output(&json!({}), format)).unwrap();
+            let locations = output["errors"]
+                .as_array()
+                .unwrap()
+                .iter()
+                .map(|error| error["keywordLocation"].as_str().unwrap())
+                .collect::<Vec<&str>>();
+
+            assert_eq!(locations, ["/else/required"], "{format:?}");
+        }
+    }
+
+    /// https://json-schema.org/learn/miscellaneous-examples#conditional-validation-with-dependentschemas
+    #[test]
+    fn dependent_keywords_example() {
+        let schema: Schema = serde_json::from_str(
+            r#"{
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "credit_card": { "type": "number" },
+                    "billing_address": { "type": "string" }
+                },
+                "dependentRequired": {
+                    "credit_card": ["billing_address"]
+                },
+                "dependentSchemas": {
+                    "credit_card": {
+                        "properties": { "security_code": { "type": "string" } },
+                        "required": ["security_code"]
+                    }
+                }
+            }"#,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+
+        assert!(validator.is_valid(&json!({ "name": "John Doe" })));
+        assert_eq!(
+            kinds(
+                &schema,
+                &registry,
+                json!({ "credit_card": 5555555555555555_i64 })
+            ),
+            vec![
+                ValidationErrorKind::DependentRequired(
+                    "credit_card".to_owned(),
+                    "billing_address".to_owned()
+                ),
+                ValidationErrorKind::Required("security_code".to_owned())
+            ]
+        );
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/conditionals#dependentRequired
+    #[test]
+    fn legacy_dependencies_example() {
+        let schema = Schema::from_str_with_dialect(
+            r#"{
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "type": "object",
+                "dependencies": {
+                    "credit_card": ["billing_address"],
+                    "billing_address": {
+                        "properties": { "credit_card": { "type": "number" } },
+                        "required": ["credit_card"]
+                    }
+                }
+            }"#,
+            Dialect::Draft7,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+        let compiled = CompiledValidator::compile(&schema, &registry).unwrap();
+
+        let instances = [
+            json!({ "name": "John Doe" }),
+            json!({ "credit_card": 5555555555555555_i64, "billing_address": "555 Debtor's Lane" }),
+            json!({ "credit_card": 5555555555555555_i64 }),
+            json!({ "billing_address": "555 Debtor's Lane" }),
+        ];
+
+        for (instance, valid) in instances.iter().zip([true, true, false, false]) {
+            assert_eq!(validator.is_valid(instance), valid, "{instance}");
+            assert_eq!(compiled.is_valid(instance), valid, "{instance}");
+            assert_eq!(validator.validate(instance), compiled.validate(instance));
+        }
+
+        let errors = validator.validate(&instances[2]).unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].keyword_location(), "/dependencies/credit_card");
+        assert_eq!(
+            errors[0].kind(),
+            &ValidationErrorKind::DependentRequired(
+                "credit_card".to_owned(),
+                "billing_address".to_owned()
+            )
+        );
+        assert_eq!(
+            kinds(&schema, &registry, instances[3].clone()),
+            vec![ValidationErrorKind::Required("credit_card".to_owned())]
+        );
+    }
+
+    /// https://json-schema.org/draft/2019-09/json-schema-core#recursive-example
+    #[test]
+    fn recursive_reference_example() {
+        let tree: Schema = serde_json::from_value(json!({
+            "$schema": "https://json-schema.org/draft/2019-09/schema",
+            "$id": "https://example.com/tree",
+            "$recursiveAnchor": true,
+            "type": "object",
+            "properties": {
+                "data": true,
+                "children": { "type": "array", "items": { "$recursiveRef": "#" } }
+            }
+        }))
+        .unwrap();
+        let strict_tree: Schema = serde_json::from_value(json!({
+            "$schema": "https://json-schema.org/draft/2019-09/schema",
+            "$id": "https://example.com/strict-tree",
+            "$recursiveAnchor": true,
+            "$ref": "tree",
+            "unevaluatedProperties": false
+        }))
+        .unwrap();
+
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(tree)
+            .unwrap()
+            .add_internally_identified_schema(strict_tree)
+            .unwrap();
+        let instances = [
+            json!({ "children": [{ "data": 1, "children": [] }] }),
+            json!({ "children": [5] }),
+            json!({ "children": [{ "daat": 1 }] }),
+        ];
+
+        for (uri, valid) in [
+            ("https://example.com/tree", [true, false, true]),
+            ("https://example.com/strict-tree", [true, false, false]),
+        ] {
+            let schema = registry.get(uri).unwrap();
+            let validator = Validator::new(schema, &registry);
+            let compiled = CompiledValidator::compile(schema, &registry).unwrap();
+
+            for (instance, valid) in instances.iter().zip(valid) {
+                assert_eq!(validator.is_valid(instance), valid, "{uri} {instance}");
+                assert_eq!(compiled.is_valid(instance), valid, "{uri} {instance}");
+                assert_eq!(validator.validate(instance), compiled.validate(instance));
+            }
+        }
+
+        let strict_tree = registry.get("https://example.com/strict-tree").unwrap();
+        let errors = Validator::new(strict_tree, &registry)
+            .validate(&instances[2])
+            .unwrap_err();
+        let child = errors
+            .iter()
+            .find(|error| error.instance_location() == "/children/0/daat")
+            .unwrap();
+
+        assert_eq!(
+            child.keyword_location(),
+            "/$ref/properties/children/items/$recursiveRef/unevaluatedProperties"
+        );
+    }
+
+    /// https://json-schema.org/learn/miscellaneous-examples#arrays-of-things
+    #[test]
+    fn reference_example() {
+        let schema: Schema = serde_json::from_str(
+            r##"{
+                "$id": "https://example.com/arrays.schema.json",
+                "type": "object",
+                "properties": {
+                    "vegetables": {
+                        "type": "array",
+                        "items": { "$ref": "#/$defs/veggie" }
+                    },
+                    "children": {
+                        "type": "array",
+                        "items": { "$ref": "#" }
+                    }
+                },
+                "$defs": {
+                    "veggie": {
+                        "type": "object",
+                        "required": [ "veggieName", "veggieLike" ],
+                        "properties": {
+                            "veggieName": { "type": "string" },
+                            "veggieLike": { "type": "boolean" }
+                        }
+                    }
+                }
+            }"##,
+        )
+        .unwrap();
+
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(schema)
+            .unwrap()
+            .discover()
+            .unwrap();
+        let schema = registry
+            .get("https://example.com/arrays.schema.json")
+            .unwrap();
+        let validator = Validator::new(schema, &registry);
+
+        assert!(validator.is_valid(&json!({
+            "vegetables": [{ "veggieName": "potato", "veggieLike": true }],
+            "children": [{ "vegetables": [] }]
+        })));
+
+        let errors = validator
+            .validate(&json!({
+                "children": [{ "vegetables": [{ "veggieName": "broccoli" }] }]
+            }))
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].instance_location(), "/children/0/vegetables/0");
+        assert_eq!(
+            errors[0].keyword_location(),
+            "/properties/children/items/$ref/properties/vegetables/items/$ref/required"
+        );
+    }
+
+    /// https://json-schema.org/understanding-json-schema/structuring#bundling
+    #[test]
+    fn embedded_resource_reference_example() {
+        let schema: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/schemas/order",
+            "properties": {
+                "customer": { "$ref": "#/$defs/customer" },
+                "recipient": { "$ref": "#/$defs/customer/properties/name" }
+            },
+            "$defs": {
+                "text": { "type": "integer" },
+                "customer": {
+                    "$id": "people/customer",
+                    "properties": { "name": { "$ref": "#/$defs/text" } },
+                    "$defs": { "text": { "type": "string" } }
+                }
+            }
+        }))
+        .unwrap();
+
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(schema)
+            .unwrap()
+            .discover()
+            .unwrap();
+        let schema = registry.get("https://example.com/schemas/order").unwrap();
+        let validator = Validator::new(schema, &registry);
+        let compiled = CompiledValidator::compile(schema, &registry).unwrap();
+
+        let valid = json!({ "customer": { "name": "Ada" }, "recipient": "Ada" });
+        let invalid = json!({ "customer": { "name": 1 }, "recipient": 1 });
+
+        assert!(validator.is_valid(&valid));
+        assert!(compiled.is_valid(&valid));
+        assert_eq!(validator.validate(&invalid), compiled.validate(&invalid));
+
+        let errors = validator.validate(&invalid).unwrap_err();
+        let locations = errors
+            .iter()
+            .map(ValidationError::absolute_keyword_location)
+            .collect::<Vec<Option<&str>>>();
+
+        assert_eq!(
+            locations,
+            [Some("https://example.com/schemas/people/customer#/$defs/text/type"); 2]
+        );
+    }
+
+    /// https://json-schema.org/understanding-json-schema/structuring#defs
+    #[test]
+    fn legacy_definitions_reference_example() {
+        let schema = Schema::from_str_with_dialect(
+            r##"{
+                "$schema": "http://json-schema.org/draft-07/schema#",
+                "$id": "https://example.com/customer.schema.json",
+                "properties": {
+                    "billing_address": { "$ref": "#/definitions/address" }
+                },
+                "definitions": {
+                    "address": { "required": ["street_address", "city"] }
+                }
+            }"##,
+            Dialect::Draft7,
+        )
+        .unwrap();
+
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(schema)
+            .unwrap()
+            .discover()
+            .unwrap();
+        let schema = registry
+            .get("https://example.com/customer.schema.json")
+            .unwrap();
+        let validator = Validator::new(schema, &registry);
+
+        assert!(validator.is_valid(&json!({
+            "billing_address": { "street_address": "1600 Pennsylvania Avenue NW", "city": "Washington" }
+        })));
+
+        let errors = validator
+            .validate(&json!({ "billing_address": { "city": "Washington" } }))
+            .unwrap_err();
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].keyword_location(),
+            "/properties/billing_address/$ref/required"
+        );
+    }
+
+    /// https://json-schema.org/draft-07/draft-handrews-json-schema-01#rfc.section.8.3
+    #[test]
+    fn reference_siblings_example() {
+        for (dialect, valid) in [(Dialect::Draft7, true), (Dialect::Draft2019_09, false)] {
+            let schema = Schema::from_value_with_dialect(
+                json!({
+                    "$schema": dialect.uri(),
+                    "properties": {
+                        "name": { "$ref": "#/definitions/name", "maxLength": 2 }
+                    },
+                    "definitions": { "name": { "type": "string" } }
+                }),
+                dialect,
+            )
+            .unwrap();
+
+            let registry = SchemaRegistry::new();
+            let validator = Validator::new(&schema, &registry);
+            let compiled = CompiledValidator::compile(&schema, &registry).unwrap();
+            let instance = json!({ "name": "abcd" });
+
+            assert_eq!(validator.is_valid(&instance), valid, "{dialect:?}");
+            assert_eq!(compiled.is_valid(&instance), valid, "{dialect:?}");
+            assert!(!validator.is_valid(&json!({ "name": 1 })), "{dialect:?}");
+            assert!(!compiled.is_valid(&json!({ "name": 1 })), "{dialect:?}");
+        }
+    }
+
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-dynamic-references-with-dyn
+    #[test]
+    fn dynamic_reference_example() {
+        let tree: Schema = serde_json::from_value(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/tree",
+            "$dynamicAnchor": "node",
+            "type": "object",
+            "properties": {
+                "data": true,
+                "children": { "type": "array", "items": { "$dynamicRef": "#node" } }
+            }
+        }))
+        .unwrap();
+        let strict_tree: Schema = serde_json::from_value(json!({
+            "$schema": "https://json-schema.org/draft/2020-12/schema",
+            "$id": "https://example.com/strict-tree",
+            "$dynamicAnchor": "node",
+            "$ref": "tree",
+            "unevaluatedProperties": false
+        }))
+        .unwrap();
+
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(tree)
+            .unwrap()
+            .add_internally_identified_schema(strict_tree)
+            .unwrap();
+        let tree = registry.get("https://example.com/tree").unwrap();
+        let strict_tree = registry.get("https://example.com/strict-tree").unwrap();
+        let instance = json!({ "children": [{ "daat": 1 }] });
+
+        assert!(Validator::new(tree, &registry).is_valid(&instance));
+
+        let errors = Validator::new(strict_tree, &registry)
+            .validate(&instance)
+            .unwrap_err();
+
+        // The failing child also fails `$ref`, leaving `children` itself unevaluated.
+        let child = errors
+            .iter()
+            .find(|error| error.instance_location() == "/children/0/daat")
+            .unwrap();
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(
+            child.keyword_location(),
+            "/$ref/properties/children/items/$dynamicRef/unevaluatedProperties"
+        );
+        assert_eq!(
+            child.absolute_keyword_location(),
+            Some("https://example.com/strict-tree#/unevaluatedProperties")
+        );
+    }
+
+    /// https://www.rfc-editor.org/rfc/rfc6901#section-6
+    #[test]
+    fn percent_encoded_reference_example() {
+        let schema: Schema = serde_json::from_str(
+            r##"{
+                "$id": "https://example.com/pointers.schema.json",
+                "properties": {
+                    "first name": { "type": "string" },
+                    "nickname": { "$ref": "#/properties/first%20name" }
+                }
+            }"##,
+        )
+        .unwrap();
+
+        let registry = SchemaRegistry::new()
+            .add_internally_identified_schema(schema)
+            .unwrap()
+            .discover()
+            .unwrap();
+        let schema = registry
+            .get("https://example.com/pointers.schema.json")
+            .unwrap();
+        let validator = Validator::new(schema, &registry);
+        let compiled = CompiledValidator::compile(schema, &registry).unwrap();
+
+        assert!(validator.is_valid(&json!({ "nickname": "Zaphod" })));
+        assert!(!validator.is_valid(&json!({ "nickname": 42 })));
+        assert!(!compiled.is_valid(&json!({ "nickname": 42 })));
+    }
+
+    fn polygon_registry() -> SchemaRegistry {
+        let schema: Schema = serde_json::from_str(
+            r##"{
+                "$id": "https://example.com/polygon",
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$defs": {
+                    "point": {
+                        "type": "object",
+                        "properties": {
+                            "x": { "type": "number" },
+                            "y": { "type": "number" }
+                        },
+                        "additionalProperties": false,
+                        "required": [ "x", "y" ]
+                    }
+                },
+                "type": "array",
+                "items": { "$ref": "#/$defs/point" },
+                "minItems": 3
+            }"##,
+        )
+        .unwrap();
+
+        SchemaRegistry::new()
+            .add_internally_identified_schema(schema)
+            .unwrap()
+            .discover()
+            .unwrap()
+    }
+
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-output-structure
+    #[test]
+    fn basic_output_example() {
+        let registry = polygon_registry();
+        let schema = registry.get("https://example.com/polygon").unwrap();
+        let validator = Validator::new(schema, &registry);
+        let instance = json!([{ "x": 2.5, "y": 1.3 }, { "x": 1, "z": 6.7 }]);
+
+        let flag = serde_json::to_value(validator.output(&instance, OutputFormat::Flag)).unwrap();
+
+        assert_eq!(flag, json!({ "valid": false }));
+
+        let basic = serde_json::to_value(validator.output(&instance, OutputFormat::Basic)).unwrap();
+
+        assert_eq!(
+            basic,
+            json!({
+                "valid": false,
+                "errors": [
+                    {
+                        "valid": false,
+                        "keywordLocation": "/items/$ref/additionalProperties",
+                        "absoluteKeywordLocation":
+                            "https://example.com/polygon#/$defs/point/additionalProperties",
+                        "instanceLocation": "/1/z",
+                        "error": "No value is valid against the false schema"
+                    },
+                    {
+                        "valid": false,
+                        "keywordLocation": "/items/$ref/required",
+                        "absoluteKeywordLocation": "https://example.com/polygon#/$defs/point/required",
+                        "instanceLocation": "/1",
+                        "error": "Required property y is missing"
+                    },
+                    {
+                        "valid": false,
+                        "keywordLocation": "/minItems",
+                        "absoluteKeywordLocation": "https://example.com/polygon#/minItems",
+                        "instanceLocation": "",
+                        "error": "Array has fewer than 3 items"
+                    }
+                ]
+            })
+        );
+    }
+
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-detailed
+    #[test]
+    fn detailed_output_example() {
+        let registry = polygon_registry();
+        let schema = registry.get("https://example.com/polygon").unwrap();
+        let validator = Validator::new(schema, &registry);
+        let instance = json!([{ "x": 2.5, "y": 1.3 }, { "x": 1, "z": 6.7 }]);
+
+        let detailed =
+            serde_json::to_value(validator.output(&instance, OutputFormat::Detailed)).unwrap();
+
+        assert_eq!(
+            detailed,
+            json!({
+                "valid": false,
+                "keywordLocation": "",
+                "absoluteKeywordLocation": "https://example.com/polygon",
+                "instanceLocation": "",
+                "errors": [
+                    {
+                        "valid": false,
+                        "keywordLocation": "/items/$ref",
+                        "absoluteKeywordLocation": "https://example.com/polygon#/$defs/point",
+                        "instanceLocation": "/1",
+                        "errors": [
+                            {
+                                "valid": false,
+                                "keywordLocation": "/items/$ref/additionalProperties",
+                                "absoluteKeywordLocation":
+                                    "https://example.com/polygon#/$defs/point/additionalProperties",
+                                "instanceLocation": "/1/z",
+                                "error": "No value is valid against the false schema"
+                            },
+                            {
+                                "valid": false,
+                                "keywordLocation": "/items/$ref/required",
+                                "absoluteKeywordLocation":
+                                    "https://example.com/polygon#/$defs/point/required",
+                                "instanceLocation": "/1",
+                                "error": "Required property y is missing"
+                            }
+                        ]
+                    },
+                    {
+                        "valid": false,
+                        "keywordLocation": "/minItems",
+                        "absoluteKeywordLocation": "https://example.com/polygon#/minItems",
+                        "instanceLocation": "",
+                        "error": "Array has fewer than 3 items"
+                    }
+                ]
+            })
+        );
+    }
+
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-verbose
+    #[test]
+    fn verbose_output_example() {
+        let schema: Schema = serde_json::from_str(
+            r#"{
+                "title": "Point",
+                "properties": {
+                    "x": { "type": "number" }
+                }
+            }"#,
+        )
+        .unwrap();
+        let registry = SchemaRegistry::new();
+        let validator = Validator::new(&schema, &registry);
+
+        let verbose =
+            serde_json::to_value(validator.output(&json!({ "x": 1 }), OutputFormat::Verbose))
+                .unwrap();
+
+        assert_eq!(
+            verbose,
+            json!({
+                "valid": true,
+                "keywordLocation": "",
+                "instanceLocation": "",
+                "annotations": [
+                    {
+                        "valid": true,
+                        "keywordLocation": "/properties",
+                        "instanceLocation": "",
+                        "annotation": ["x"],
+                        "annotations": [
+                            {
+                                "valid": true,
+                                "keywordLocation": "/properties/x",
+                                "instanceLocation": "/x",
+                                "annotations": [
+                                    {
+                                        "valid": true,
+                                        "keywordLocation": "/properties/x/type",
+                                        "instanceLocation": "/x"
+                                    }
+                                ]
+                            }
+                        ]
+                    },
+                    {
+                        "valid": true,
+                        "keywordLocation": "/title",
+                        "instanceLocation": "",
+                        "annotation": "Point"
+                    }
+                ]
+            })
+        );
+
+        let basic = serde_json::to_value(validator.output(&json!({ "x": 1 }), OutputFormat::Basic))
+            .unwrap();
+
+        assert_eq!(
+            basic,
+            json!({
+                "valid": true,
+                "annotations": [
+                    {
+                        "valid": true,
+                        "keywordLocation": "/properties",
+                        "instanceLocation": "",
+                        "annotation": ["x"]
+                    },
+                    {
+                        "valid": true,
+                        "keywordLocation": "/title",
+                        "instanceLocation": "",
+                        "annotation": "Point"
+                    }
+                ]
+            })
+        );
+    }
+}
@@ -0,0 +1,258 @@
+use regex::Regex;
+use schema_registry::{IngestionValidator, SchemaRegistry, SchemaViolation};
+use serde_json_schema::{walk_schema, Dialect, JsonPointer, Schema, Visitor};
+
+use crate::{ValidationErrorKind, Validator};
+
+/// Validates schemas against the meta-schema their `$schema` declares, for
+/// [`SchemaRegistry::validate_on_ingestion`]. `format: regex` is asserted as well, so patterns
+/// that don't compile are violations too.
+pub struct MetaSchemaValidator {
+    metaschemas: SchemaRegistry,
+    default_dialect: Dialect,
+}
+
+impl MetaSchemaValidator {
+    /// Looks the meta-schemas up in the registry, e.g.
+    /// [`SchemaRegistry::with_standard_metaschemas`] with the `standard_metaschemas` feature.
+    pub fn new(metaschemas: SchemaRegistry) -> Self {
+        MetaSchemaValidator {
+            metaschemas,
+            default_dialect: Dialect::Draft2020_12,
+        }
+    }
+
+    /// The dialect whose meta-schema applies to schemas that don't declare one with `$schema`.
+    pub fn default_dialect(mut self, dialect: Dialect) -> Self {
+        self.default_dialect = dialect;
+        self
+    }
+}
+
+impl IngestionValidator for MetaSchemaValidator {
+    fn validate(&self, schema: &Schema) -> Result<(), Vec<SchemaViolation>> {
+        let uri = schema
+            .schema
+            .as_deref()
+            .unwrap_or(self.default_dialect.uri());
+        let Some(metaschema) = self.metaschemas.resolve("", uri) else {
+            return Err(vec![SchemaViolation::new(
+                JsonPointer::from_iter(["$schema"]),
+                format!("The meta-schema {uri} is not available"),
+            )]);
+        };
+
+        let instance = serde_json::to_value(schema)
+            .map_err(|error| vec![SchemaViolation::new(JsonPointer::root(), error.to_string())])?;
+
+        let mut violations: Vec<SchemaViolation> =
+            Validator::new(metaschema.schema(), &self.metaschemas)
+                .validate(&instance)
+                .err()
+                .unwrap_or_default()
+                .into_iter()
+                .map(|error| {
+                    let location =
+                        JsonPointer::parse(error.instance_location()).unwrap_or_default();
+                    SchemaViolation::new(location, error.kind().to_string())
+                })
+                .collect();
+
+        let mut patterns = InvalidPatterns::default();
+        patterns.visit_schema(&JsonPointer::root(), schema);
+        violations.extend(patterns.violations);
+
+        match violations.is_empty() {
+            true => Ok(()),
+            false => Err(violations),
+        }
+    }
+}
+
+/// Collects the `pattern`s and `patternProperties` that aren't valid regular expressions.
+#[derive(Default)]
+struct InvalidPatterns {
+    violations: Vec<SchemaViolation>,
+}
+
+impl InvalidPatterns {
+    fn check(&mut self, location: JsonPointer, pattern: &str) {
+        if Regex::new(pattern).is_err() {
+            let kind = ValidationErrorKind::InvalidPattern(pattern.to_owned());
+            self.violations
+                .push(SchemaViolation::new(location, kind.to_string()));
+        }
+    }
+}
+
+impl<'a> Visitor<'a> for InvalidPatterns {
+    fn visit_schema(&mut self, pointer: &JsonPointer, schema: &'a Schema) {
+        if let Some(pattern) = &schema.pattern {
+            self.check(pointer.join(&JsonPointer::from_iter(["pattern"])), pattern);
+        }
+
+        for pattern in schema
+            .pattern_properties
+            .iter()
+            .flat_map(|properties| properties.keys())
+        {
+            let location = JsonPointer::from_iter(["patternProperties", pattern.as_str()]);
+            self.check(pointer.join(&location), pattern);
+        }
+
+        walk_schema(self, pointer, schema);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use schema_registry::SchemaRegistryIngestionError;
+    use serde_json::json;
+
+    use super::*;
+
+    fn registry() -> SchemaRegistry {
+        let validator = MetaSchemaValidator::new(SchemaRegistry::with_standard_metaschemas());
+
+        SchemaRegistry::new().validate_on_ingestion(validator)
+    }
+
+    fn violations(schema: serde_json::Value) -> Vec<(String, String)> {
+        let schema = Schema::from_value_with_dialect(schema, Dialect::Draft2020_12).unwrap();
+
+        match registry().add_internally_identified_schema(schema) {
+            Ok(_) => Vec::new(),
+            Err(SchemaRegistryIngestionError::InvalidSchema(violations)) => violations
+                .into_iter()
+                .map(|violation| {
+                    (
+                        violation.location().to_string(),
+                        violation.message().to_owned(),
+                    )
+                })
+                .collect(),
+            Err(error) => panic!("{error}"),
+        }
+    }
+
+    #[test]
+    fn metaschema_validation_example() {
+        assert_eq!(
+            violations(json!({
+                "$id": "https://example.com/product.schema.json",
+                "properties": {
+                    "name": { "type": "string", "minLength": 1, "pattern": "^[A-Z]" },
+                    "tags": { "type": "array", "items": { "type": "string" } }
+                },
+                "required": ["name"]
+            })),
+            vec![]
+        );
+
+        assert_eq!(
+            violations(json!({
+                "$id": "https://example.com/product.schema.json",
+                "properties": {
+                    "name": { "type": "string", "minLength": -1 },
+                    "sku": { "type": "string", "pattern": "^[A-Z" }
+                },
+                "patternProperties": { "(": true },
+                "required": ["name", "sku", "name"]
+            })),
+            vec![
+                (
+                    "/properties/name/minLength".to_owned(),
+                    "Value is less than the minimum of 0".to_owned()
+                ),
+                (
+                    "/required".to_owned(),
+                    "Array items are not unique".to_owned()
+                ),
+                (
+                    "/patternProperties/(".to_owned(),
+                    "Pattern ( is not a valid regular expression".to_owned()
+                ),
+                (
+                    "/properties/sku/pattern".to_owned(),
+                    "Pattern ^[A-Z is not a valid regular expression".to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn legacy_metaschema_validation_example() {
+        for dialect in [Dialect::Draft4, Dialect::Draft6, Dialect::Draft7] {
+            let valid = json!({
+                "$schema": dialect.uri(),
+                "definitions": { "name": { "type": "string", "maxLength": 80 } },
+                "properties": { "name": { "$ref": "#/definitions/name" } }
+            });
+            let invalid = json!({
+                "$schema": dialect.uri(),
+                "properties": { "name": { "type": "text" } }
+            });
+
+            let validator = MetaSchemaValidator::new(SchemaRegistry::with_standard_metaschemas());
+            let valid = Schema::from_value_with_dialect(valid, dialect).unwrap();
+            let invalid = Schema::from_value_with_dialect(invalid, dialect).unwrap();
+
+            assert_eq!(validator.validate(&valid), Ok(()));
+            assert!(validator.validate(&invalid).is_err());
+        }
+
+        // The draft-04 meta-schema requires `maximum` with `exclusiveMaximum` through
+        // `dependencies`, and the 2019-09 one reaches subschemas through `$recursiveRef`.
+        let invalid = [
+            (
+                Dialect::Draft4,
+                json!({ "properties": { "price": { "exclusiveMaximum": true } } }),
+                "/properties/price",
+            ),
+            (
+                Dialect::Draft7,
+                json!({ "properties": { "address": { "properties": { "zip": { "minLength": -4 } } } } }),
+                "/properties/address/properties/zip/minLength",
+            ),
+            (
+                Dialect::Draft2019_09,
+                json!({ "properties": { "name": { "type": "text" } } }),
+                "/properties/name/type",
+            ),
+            (
+                Dialect::Draft2019_09,
+                json!({ "properties": { "address": { "properties": { "zip": { "minLength": -4 } } } } }),
+                "/properties/address/properties/zip/minLength",
+            ),
+        ];
+
+        for (dialect, invalid, location) in invalid {
+            let mut invalid = invalid;
+            invalid["$schema"] = json!(dialect.uri());
+
+            let validator = MetaSchemaValidator::new(SchemaRegistry::with_standard_metaschemas());
+            let invalid = Schema::from_value_with_dialect(invalid, dialect).unwrap();
+            let violations = validator.validate(&invalid).unwrap_err();
+
+            assert!(
+                violations
+                    .iter()
+                    .any(|violation| violation.location().to_string() == location),
+                "{dialect:?} {violations:?}"
+            );
+        }
+
+        let unknown: Schema = serde_json::from_value(json!({
+            "$schema": "https://example.com/custom-metaschema"
+        }))
+        .unwrap();
+        let validator = MetaSchemaValidator::new(SchemaRegistry::new());
+
+        assert_eq!(
+            validator.validate(&unknown).unwrap_err()[0]
+                .location()
+                .to_string(),
+            "/$schema"
+        );
+    }
+}
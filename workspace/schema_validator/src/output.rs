@@ -0,0 +1,180 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{ValidationError, ValidationErrorKind};
+
+/// The output formats of https://json-schema.org/draft/2020-12/json-schema-core#name-output-formatting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Flag,
+    Basic,
+    Detailed,
+    Verbose,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OutputUnit {
+    pub valid: bool,
+
+    #[serde(rename = "keywordLocation", skip_serializing_if = "Option::is_none")]
+    pub keyword_location: Option<String>,
+
+    #[serde(
+        rename = "absoluteKeywordLocation",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub absolute_keyword_location: Option<String>,
+
+    #[serde(rename = "instanceLocation", skip_serializing_if = "Option::is_none")]
+    pub instance_location: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub annotation: Option<Value>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<OutputUnit>,
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub annotations: Vec<OutputUnit>,
+}
+
+/// The result of applying a schema or keyword to an instance location, from which every output
+/// format is derived.
+#[derive(Debug, Clone)]
+pub(crate) struct Node {
+    pub(crate) valid: bool,
+    pub(crate) keyword_location: String,
+    pub(crate) absolute_keyword_location: Option<String>,
+    pub(crate) instance_location: String,
+    pub(crate) kind: Option<ValidationErrorKind>,
+    pub(crate) annotation: Option<Value>,
+    pub(crate) children: Vec<Node>,
+    /// Whether the node is only applied for its annotations, like `if`, so that its failure is no
+    /// error of its parent.
+    pub(crate) annotation_only: bool,
+}
+
+impl Node {
+    pub(crate) fn output(&self, format: OutputFormat) -> OutputUnit {
+        match format {
+            OutputFormat::Flag => OutputUnit {
+                valid: self.valid,
+                keyword_location: None,
+                absolute_keyword_location: None,
+                instance_location: None,
+                error: None,
+                annotation: None,
+                errors: Vec::new(),
+                annotations: Vec::new(),
+            },
+            OutputFormat::Basic => {
+                let mut units = Vec::new();
+                self.flatten(!self.valid, &mut units);
+
+                let (errors, annotations) = match self.valid {
+                    true => (Vec::new(), units),
+                    false => (units, Vec::new()),
+                };
+
+                OutputUnit {
+                    errors,
+                    annotations,
+                    ..self.output(OutputFormat::Flag)
+                }
+            }
+            OutputFormat::Detailed => self.detailed(),
+            OutputFormat::Verbose => self.verbose(),
+        }
+    }
+
+    /// Collects the errors that explain why this node failed, without descending into keywords
+    /// that already carry their own error such as `anyOf` or `not`.
+    pub(crate) fn collect_errors(&self, errors: &mut Vec<ValidationError>) {
+        if self.valid {
+            return;
+        }
+
+        match &self.kind {
+            Some(kind) => errors.push(ValidationError {
+                instance_location: self.instance_location.clone(),
+                keyword_location: self.keyword_location.clone(),
+                absolute_keyword_location: self.absolute_keyword_location.clone(),
+                kind: kind.clone(),
+            }),
+            None => self
+                .children
+                .iter()
+                .filter(|child| !child.annotation_only)
+                .for_each(|child| child.collect_errors(errors)),
+        }
+    }
+
+    fn unit(&self) -> OutputUnit {
+        OutputUnit {
+            valid: self.valid,
+            keyword_location: Some(self.keyword_location.clone()),
+            absolute_keyword_location: self.absolute_keyword_location.clone(),
+            instance_location: Some(self.instance_location.clone()),
+            error: self.kind.as_ref().map(|kind| kind.to_string()),
+            annotation: self.annotation.clone(),
+            errors: Vec::new(),
+            annotations: Vec::new(),
+        }
+    }
+
+    /// Lists the failing units when collecting errors, otherwise the units with annotations.
+    fn flatten(&self, errors: bool, units: &mut Vec<OutputUnit>) {
+        if self.valid == errors || (errors && self.annotation_only) {
+            return;
+        }
+
+        let relevant = match errors {
+            true => self.kind.is_some(),
+            false => self.annotation.is_some(),
+        };
+
+        if relevant {
+            units.push(self.unit());
+        }
+
+        self.children
+            .iter()
+            .for_each(|child| child.flatten(errors, units));
+    }
+
+    fn detailed(&self) -> OutputUnit {
+        let mut unit = self.unit();
+
+        if !self.valid {
+            unit.errors = self
+                .children
+                .iter()
+                .filter(|child| !child.valid && !child.annotation_only)
+                .map(|child| child.detailed())
+                .map(|mut child| {
+                    while child.error.is_none() && child.errors.len() == 1 {
+                        child = child.errors.remove(0);
+                    }
+                    child
+                })
+                .collect();
+        }
+
+        unit
+    }
+
+    fn verbose(&self) -> OutputUnit {
+        let mut unit = self.unit();
+        let children = self.children.iter().map(|child| child.verbose()).collect();
+
+        match self.valid {
+            true => unit.annotations = children,
+            false => unit.errors = children,
+        }
+
+        unit
+    }
+}
@@ -1,8 +1,25 @@
-use std::collections::HashMap;
-
-use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{self, MapAccess};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+mod pointer;
+mod upgrade;
+mod visit;
+
+pub use pointer::{percent_decode, JsonPointer, JsonPointerError};
+pub use upgrade::{UpgradeIssue, UpgradeIssueKind};
+pub use visit::{
+    fold_subschemas, walk_schema, walk_schema_mut, Fold, Position, Visitor, VisitorMut,
+};
 
 pub const DEFINITIONS_PATH: &str = "$defs";
+pub const LEGACY_DEFINITIONS_PATH: &str = "definitions";
 pub const PROPERTIES_PATH: &str = "properties";
 pub const ITEMS_PATH: &str = "items";
 pub const DEPENDENT_SCHEMAS_PATH: &str = "dependentSchemas";
@@ -19,6 +36,9 @@ pub struct Schema {
     #[serde(rename = "$schema", skip_serializing_if = "Option::is_none")]
     pub schema: Option<String>,
 
+    #[serde(rename = "$vocabulary", skip_serializing_if = "Option::is_none")]
+    pub vocabulary: Option<HashMap<String, bool>>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
     pub title: Option<String>,
 
@@ -26,13 +46,16 @@ pub struct Schema {
     pub schema_type: Option<StringOrStringArray>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub properties: Option<HashMap<String, Schema>>,
+    pub properties: Option<HashMap<String, BooleanOrSchema>>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub minimum: Option<i64>,
+    pub minimum: Option<Number>,
+
+    #[serde(rename = "exclusiveMinimum", skip_serializing_if = "Option::is_none")]
+    pub exclusive_minimum: Option<BooleanOrNumber>,
 
     #[serde(rename = "minLength", skip_serializing_if = "Option::is_none")]
     pub min_length: Option<i64>,
@@ -41,13 +64,17 @@ pub struct Schema {
     pub max_length: Option<i64>,
 
     #[serde(rename = "$defs", skip_serializing_if = "Option::is_none")]
-    pub definitions: Option<HashMap<String, Schema>>,
+    pub definitions: Option<HashMap<String, BooleanOrSchema>>,
+
+    /// Draft 7 and earlier name `$defs` `definitions`.
+    #[serde(rename = "definitions", skip_serializing_if = "Option::is_none")]
+    pub legacy_definitions: Option<HashMap<String, BooleanOrSchema>>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub required: Option<Vec<String>>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub items: Option<BooleanOrSchema>,
+    pub items: Option<SchemaOrSchemaArray>,
 
     #[serde(rename = "additionalItems", skip_serializing_if = "Option::is_none")]
     pub additional_items: Option<BooleanOrSchema>,
@@ -55,6 +82,12 @@ pub struct Schema {
     #[serde(rename = "$ref", skip_serializing_if = "Option::is_none")]
     pub reference: Option<String>,
 
+    #[serde(rename = "$dynamicRef", skip_serializing_if = "Option::is_none")]
+    pub dynamic_reference: Option<String>,
+
+    #[serde(rename = "$recursiveRef", skip_serializing_if = "Option::is_none")]
+    pub recursive_reference: Option<String>,
+
     #[serde(rename = "enum", skip_serializing_if = "Option::is_none")]
     pub schema_enum: Option<Vec<AnyType>>,
 
@@ -65,49 +98,59 @@ pub struct Schema {
     pub dependent_required: Option<HashMap<String, Vec<String>>>,
 
     #[serde(rename = "dependentSchemas", skip_serializing_if = "Option::is_none")]
-    pub dependent_schemas: Option<HashMap<String, Schema>>,
+    pub dependent_schemas: Option<HashMap<String, BooleanOrSchema>>,
+
+    /// Draft 7 and earlier combine `dependentRequired` and `dependentSchemas` in one keyword.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dependencies: Option<HashMap<String, SchemaOrStringArray>>,
 
     #[serde(rename = "const", skip_serializing_if = "Option::is_none")]
     pub schema_const: Option<AnyType>,
 
     #[serde(rename = "if", skip_serializing_if = "Option::is_none")]
-    pub schema_if: Option<Box<Schema>>,
+    pub schema_if: Option<BooleanOrSchema>,
 
     #[serde(rename = "then", skip_serializing_if = "Option::is_none")]
-    pub schema_then: Option<Box<Schema>>,
+    pub schema_then: Option<BooleanOrSchema>,
 
     #[serde(rename = "else", skip_serializing_if = "Option::is_none")]
-    pub schema_else: Option<Box<Schema>>,
+    pub schema_else: Option<BooleanOrSchema>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
     pub format: Option<String>,
 
     #[serde(rename = "oneOf", skip_serializing_if = "Option::is_none")]
-    pub one_of: Option<Vec<Schema>>,
+    pub one_of: Option<Vec<BooleanOrSchema>>,
 
     #[serde(rename = "allOf", skip_serializing_if = "Option::is_none")]
-    pub all_of: Option<Vec<Schema>>,
+    pub all_of: Option<Vec<BooleanOrSchema>>,
 
     #[serde(rename = "anyOf", skip_serializing_if = "Option::is_none")]
-    pub any_of: Option<Vec<Schema>>,
+    pub any_of: Option<Vec<BooleanOrSchema>>,
 
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub not: Option<Box<Schema>>,
+    pub not: Option<BooleanOrSchema>,
 
     #[serde(rename = "$anchor", skip_serializing_if = "Option::is_none")]
     pub anchor: Option<String>,
 
+    #[serde(rename = "$dynamicAnchor", skip_serializing_if = "Option::is_none")]
+    pub dynamic_anchor: Option<String>,
+
+    #[serde(rename = "$recursiveAnchor", skip_serializing_if = "Option::is_none")]
+    pub recursive_anchor: Option<bool>,
+
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub maximum: Option<i64>,
+    pub maximum: Option<Number>,
 
     #[serde(rename = "multipleOf", skip_serializing_if = "Option::is_none")]
-    pub multiple_of: Option<IntegerOrNumber>,
+    pub multiple_of: Option<Number>,
 
     #[serde(rename = "exclusiveMaximum", skip_serializing_if = "Option::is_none")]
-    pub exlusive_maximum: Option<BooleanOrIntegerOrNumber>,
+    pub exlusive_maximum: Option<BooleanOrNumber>,
 
     #[serde(rename = "patternProperties", skip_serializing_if = "Option::is_none")]
-    pub pattern_properties: Option<HashMap<String, Schema>>,
+    pub pattern_properties: Option<HashMap<String, BooleanOrSchema>>,
 
     #[serde(
         rename = "additionalProperties",
@@ -122,7 +165,7 @@ pub struct Schema {
     pub unevaluated_properties: Option<BooleanOrSchema>,
 
     #[serde(rename = "propertyNames", skip_serializing_if = "Option::is_none")]
-    pub property_names: Option<PropertyNames>,
+    pub property_names: Option<BooleanOrSchema>,
 
     #[serde(rename = "minProperties", skip_serializing_if = "Option::is_none")]
     pub min_properties: Option<i64>,
@@ -131,7 +174,7 @@ pub struct Schema {
     pub max_properties: Option<i64>,
 
     #[serde(rename = "prefixItems", skip_serializing_if = "Option::is_none")]
-    pub prefix_items: Option<Vec<Schema>>,
+    pub prefix_items: Option<Vec<BooleanOrSchema>>,
 
     #[serde(rename = "unevaluatedItems", skip_serializing_if = "Option::is_none")]
     pub unevaluated_items: Option<BooleanOrSchema>,
@@ -177,29 +220,430 @@ pub struct Schema {
 
     #[serde(rename = "contentMediaType", skip_serializing_if = "Option::is_none")]
     pub content_media_type: Option<String>,
+
+    #[serde(rename = "contentSchema", skip_serializing_if = "Option::is_none")]
+    pub content_schema: Option<BooleanOrSchema>,
+
+    /// Keywords without a dedicated field, such as vendor extensions (`x-*`) or OpenAPI's
+    /// `discriminator`, kept so they survive a round trip.
+    #[serde(flatten)]
+    pub extra: BTreeMap<String, Value>,
 }
 
 impl Schema {
+    /// Deserializes a schema and interprets every keyword according to the draft it declares in
+    /// `$schema`, or `default` when it declares none. Keywords that the draft doesn't define end up
+    /// in `extra`, and keywords written in another draft's form are rejected.
+    pub fn from_value_with_dialect(value: Value, default: Dialect) -> Result<Schema, DialectError> {
+        let mut schema: Schema =
+            serde_json::from_value(value).map_err(DialectError::Deserialization)?;
+        schema.interpret(default)?;
+
+        Ok(schema)
+    }
+
+    /// Like [`Schema::from_value_with_dialect`], but deserializes straight from the text, so that
+    /// errors in it come with a line and column.
+    pub fn from_str_with_dialect(json: &str, default: Dialect) -> Result<Schema, DialectError> {
+        let mut schema: Schema =
+            serde_json::from_str(json).map_err(DialectError::Deserialization)?;
+        schema.interpret(default)?;
+
+        Ok(schema)
+    }
+
+    /// The draft declared by `$schema`, if it is one of the known drafts.
+    pub fn dialect(&self) -> Option<Dialect> {
+        self.schema.as_deref().and_then(Dialect::from_uri)
+    }
+
     pub fn get_id(&self) -> Option<String> {
         self.dollar_id.to_owned().or_else(|| self.id.to_owned())
     }
+
+    pub fn get_extra(&self, keyword: &str) -> Option<&Value> {
+        self.extra.get(keyword)
+    }
+
+    pub fn insert_extra(&mut self, keyword: impl Into<String>, value: Value) -> Option<Value> {
+        self.extra.insert(keyword.into(), value)
+    }
+
+    pub fn remove_extra(&mut self, keyword: &str) -> Option<Value> {
+        self.extra.remove(keyword)
+    }
+
+    /// The vendor extensions of this schema, i.e. the unknown keywords prefixed with `x-`.
+    pub fn extensions(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.extra
+            .iter()
+            .filter(|(keyword, _)| keyword.starts_with("x-"))
+    }
+
+    fn interpret(&mut self, default: Dialect) -> Result<(), DialectError> {
+        let dialect = self.dialect().unwrap_or(default);
+        let extra = &mut self.extra;
+
+        match dialect {
+            Dialect::Draft4 => demote(extra, "$id", self.dollar_id.take()),
+            _ => demote(extra, "id", self.id.take()),
+        }
+
+        // `definitions` stays meaningful in later drafts, which reserve it for compatibility.
+        if dialect <= Dialect::Draft7 {
+            demote(extra, "$defs", self.definitions.take());
+            demote(extra, "dependentRequired", self.dependent_required.take());
+            demote(extra, "dependentSchemas", self.dependent_schemas.take());
+        } else {
+            demote(extra, "dependencies", self.dependencies.take());
+        }
+
+        if dialect < Dialect::Draft2020_12 {
+            demote(extra, "prefixItems", self.prefix_items.take());
+        } else {
+            demote(extra, "additionalItems", self.additional_items.take());
+
+            if let Some(SchemaOrSchemaArray::Array(_)) = self.items {
+                return Err(DialectError::InvalidKeyword(dialect, "items"));
+            }
+        }
+
+        let limits = [
+            ("exclusiveMinimum", &self.exclusive_minimum),
+            ("exclusiveMaximum", &self.exlusive_maximum),
+        ];
+
+        for (keyword, limit) in limits {
+            let is_boolean = match limit {
+                Some(BooleanOrNumber::Boolean(_)) => true,
+                Some(BooleanOrNumber::Number(_)) => false,
+                None => continue,
+            };
+
+            if is_boolean != (dialect == Dialect::Draft4) {
+                return Err(DialectError::InvalidKeyword(dialect, keyword));
+            }
+        }
+
+        self.subschemas_mut()
+            .into_iter()
+            .filter_map(|(_, subschema)| subschema.as_schema_mut())
+            .try_for_each(|subschema| subschema.interpret(dialect))
+    }
+
+    /// The subschemas directly nested in this schema with their position in it, e.g. `allOf/0` or
+    /// `properties/name`, ordered by position so that every walk is deterministic.
+    pub fn subschemas(&self) -> Vec<(Position<'_>, &BooleanOrSchema)> {
+        let mut subschemas = Vec::new();
+
+        let maps = [
+            (PROPERTIES_PATH, &self.properties),
+            (PATTERN_PROPERTIES_PATH, &self.pattern_properties),
+            (DEFINITIONS_PATH, &self.definitions),
+            (LEGACY_DEFINITIONS_PATH, &self.legacy_definitions),
+            (DEPENDENT_SCHEMAS_PATH, &self.dependent_schemas),
+        ];
+
+        for (keyword, map) in maps {
+            subschemas.extend(
+                map.iter()
+                    .flatten()
+                    .map(|(key, schema)| (Position::Entry(keyword, key), schema)),
+            );
+        }
+
+        let arrays = [
+            ("allOf", &self.all_of),
+            ("anyOf", &self.any_of),
+            ("oneOf", &self.one_of),
+            ("prefixItems", &self.prefix_items),
+        ];
+
+        for (keyword, array) in arrays {
+            subschemas.extend(
+                array
+                    .iter()
+                    .flatten()
+                    .enumerate()
+                    .map(|(index, schema)| (Position::Index(keyword, index), schema)),
+            );
+        }
+
+        let singles = [
+            ("additionalItems", &self.additional_items),
+            ("additionalProperties", &self.additional_properties),
+            ("unevaluatedItems", &self.unevaluated_items),
+            ("unevaluatedProperties", &self.unevaluated_properties),
+            ("propertyNames", &self.property_names),
+            ("contains", &self.contains),
+            ("contentSchema", &self.content_schema),
+            ("not", &self.not),
+            ("if", &self.schema_if),
+            ("then", &self.schema_then),
+            ("else", &self.schema_else),
+        ];
+
+        for (keyword, single) in singles {
+            subschemas.extend(
+                single
+                    .as_ref()
+                    .map(|schema| (Position::Keyword(keyword), schema)),
+            );
+        }
+
+        match &self.items {
+            Some(SchemaOrSchemaArray::Schema(items)) => {
+                subschemas.push((Position::Keyword(ITEMS_PATH), items))
+            }
+            Some(SchemaOrSchemaArray::Array(items)) => subschemas.extend(
+                items
+                    .iter()
+                    .enumerate()
+                    .map(|(index, schema)| (Position::Index(ITEMS_PATH, index), schema)),
+            ),
+            None => {}
+        }
+
+        subschemas.extend(
+            self.dependencies
+                .iter()
+                .flatten()
+                .filter_map(|(key, dependency)| match dependency {
+                    SchemaOrStringArray::Schema(schema) => {
+                        Some((Position::Entry("dependencies", key), schema))
+                    }
+                    SchemaOrStringArray::Array(_) => None,
+                }),
+        );
+
+        subschemas.sort_by_key(|(position, _)| *position);
+        subschemas
+    }
+
+    /// Like [`Schema::subschemas`], for rewriting the subschemas in place.
+    fn subschemas_mut(&mut self) -> Vec<(Position<'_>, &mut BooleanOrSchema)> {
+        let mut subschemas = Vec::new();
+
+        let maps = [
+            (PROPERTIES_PATH, &mut self.properties),
+            (PATTERN_PROPERTIES_PATH, &mut self.pattern_properties),
+            (DEFINITIONS_PATH, &mut self.definitions),
+            (LEGACY_DEFINITIONS_PATH, &mut self.legacy_definitions),
+            (DEPENDENT_SCHEMAS_PATH, &mut self.dependent_schemas),
+        ];
+
+        for (keyword, map) in maps {
+            subschemas.extend(
+                map.iter_mut()
+                    .flatten()
+                    .map(|(key, schema)| (Position::Entry(keyword, key), schema)),
+            );
+        }
+
+        let arrays = [
+            ("allOf", &mut self.all_of),
+            ("anyOf", &mut self.any_of),
+            ("oneOf", &mut self.one_of),
+            ("prefixItems", &mut self.prefix_items),
+        ];
+
+        for (keyword, array) in arrays {
+            subschemas.extend(
+                array
+                    .iter_mut()
+                    .flatten()
+                    .enumerate()
+                    .map(|(index, schema)| (Position::Index(keyword, index), schema)),
+            );
+        }
+
+        let singles = [
+            ("additionalItems", &mut self.additional_items),
+            ("additionalProperties", &mut self.additional_properties),
+            ("unevaluatedItems", &mut self.unevaluated_items),
+            ("unevaluatedProperties", &mut self.unevaluated_properties),
+            ("propertyNames", &mut self.property_names),
+            ("contains", &mut self.contains),
+            ("contentSchema", &mut self.content_schema),
+            ("not", &mut self.not),
+            ("if", &mut self.schema_if),
+            ("then", &mut self.schema_then),
+            ("else", &mut self.schema_else),
+        ];
+
+        for (keyword, single) in singles {
+            subschemas.extend(
+                single
+                    .as_mut()
+                    .map(|schema| (Position::Keyword(keyword), schema)),
+            );
+        }
+
+        match &mut self.items {
+            Some(SchemaOrSchemaArray::Schema(items)) => {
+                subschemas.push((Position::Keyword(ITEMS_PATH), items))
+            }
+            Some(SchemaOrSchemaArray::Array(items)) => subschemas.extend(
+                items
+                    .iter_mut()
+                    .enumerate()
+                    .map(|(index, schema)| (Position::Index(ITEMS_PATH, index), schema)),
+            ),
+            None => {}
+        }
+
+        subschemas.extend(self.dependencies.iter_mut().flatten().filter_map(
+            |(key, dependency)| match dependency {
+                SchemaOrStringArray::Schema(schema) => {
+                    Some((Position::Entry("dependencies", key), schema))
+                }
+                SchemaOrStringArray::Array(_) => None,
+            },
+        ));
+
+        subschemas.sort_by_key(|(position, _)| *position);
+        subschemas
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-pub struct PropertyNames {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub pattern: Option<String>,
+/// Moves a keyword the dialect doesn't define into `extra`, keeping it for a round trip.
+fn demote<T: Serialize>(extra: &mut BTreeMap<String, Value>, keyword: &str, value: Option<T>) {
+    if let Some(value) = value.and_then(|value| serde_json::to_value(value).ok()) {
+        extra.insert(keyword.to_owned(), value);
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Any JSON value, as used by `const`, `enum`, `default` and `examples`. Numbers keep their
+/// integer or float form, and equality follows JSON Schema, so `1` equals `1.0` and object keys
+/// are unordered.
+#[derive(Debug, Serialize, Clone)]
 #[serde(untagged)]
 pub enum AnyType {
+    Null,
+    Boolean(bool),
+    Number(Number),
     String(String),
     Array(Vec<AnyType>),
-    Integer(i64),
-    Number(f64),
-    Boolean(bool),
-    Null(Option<()>),
+    Object(BTreeMap<String, AnyType>),
+}
+
+impl AnyType {
+    pub fn is_null(&self) -> bool {
+        matches!(self, AnyType::Null)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            AnyType::Boolean(value) => Some(*value),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            AnyType::Number(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            AnyType::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<AnyType>> {
+        match self {
+            AnyType::Array(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&BTreeMap<String, AnyType>> {
+        match self {
+            AnyType::Object(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn from_value(value: Value) -> Self {
+        match value {
+            Value::Null => AnyType::Null,
+            Value::Bool(value) => AnyType::Boolean(value),
+            Value::Number(value) => AnyType::Number(Number(value)),
+            Value::String(value) => AnyType::String(value),
+            Value::Array(values) => {
+                AnyType::Array(values.into_iter().map(AnyType::from_value).collect())
+            }
+            Value::Object(entries) => AnyType::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, AnyType::from_value(value)))
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl PartialEq for AnyType {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (AnyType::Null, AnyType::Null) => true,
+            (AnyType::Boolean(left), AnyType::Boolean(right)) => left == right,
+            (AnyType::Number(left), AnyType::Number(right)) => {
+                left == right
+                    || match (left.as_i64(), right.as_i64()) {
+                        (Some(left), Some(right)) => left == right,
+                        _ => match (left.as_u64(), right.as_u64()) {
+                            (Some(left), Some(right)) => left == right,
+                            _ => left.as_f64().is_some() && left.as_f64() == right.as_f64(),
+                        },
+                    }
+            }
+            (AnyType::String(left), AnyType::String(right)) => left == right,
+            (AnyType::Array(left), AnyType::Array(right)) => left == right,
+            (AnyType::Object(left), AnyType::Object(right)) => left == right,
+            _ => false,
+        }
+    }
+}
+
+// Deserialized through `serde_json::Value`, which unlike an untagged enum keeps numbers intact
+// with the `arbitrary_precision` feature.
+impl<'de> Deserialize<'de> for AnyType {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Value::deserialize(deserializer).map(AnyType::from_value)
+    }
+}
+
+#[cfg(feature = "json_value")]
+impl From<Value> for AnyType {
+    fn from(value: Value) -> Self {
+        AnyType::from_value(value)
+    }
+}
+
+#[cfg(feature = "json_value")]
+impl From<AnyType> for Value {
+    fn from(value: AnyType) -> Self {
+        match value {
+            AnyType::Null => Value::Null,
+            AnyType::Boolean(value) => Value::Bool(value),
+            AnyType::Number(value) => Value::Number(value.into()),
+            AnyType::String(value) => Value::String(value),
+            AnyType::Array(values) => Value::Array(values.into_iter().map(Value::from).collect()),
+            AnyType::Object(entries) => Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, Value::from(value)))
+                    .collect(),
+            ),
+        }
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -209,28 +653,262 @@ pub enum StringOrStringArray {
     Array(Vec<String>),
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
-#[serde(untagged)]
-pub enum IntegerOrNumber {
-    Integer(i64),
-    Number(f64),
+/// A JSON number as written in a schema. Integers and floats keep their distinction, and with
+/// the `arbitrary_precision` feature the number keeps its exact textual form, so values such as
+/// `123456789012345678901234567890` or `0.1000000000000000000001` survive a round trip.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+#[serde(transparent)]
+pub struct Number(serde_json::Number);
+
+impl Number {
+    pub fn from_f64(value: f64) -> Option<Number> {
+        serde_json::Number::from_f64(value).map(Number)
+    }
+
+    pub fn is_i64(&self) -> bool {
+        self.0.is_i64()
+    }
+
+    pub fn is_u64(&self) -> bool {
+        self.0.is_u64()
+    }
+
+    pub fn is_f64(&self) -> bool {
+        self.0.is_f64()
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        self.0.as_i64()
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        self.0.as_u64()
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        self.0.as_f64()
+    }
+
+    /// The exact textual form of the number as it appeared in the schema.
+    #[cfg(feature = "arbitrary_precision")]
+    pub fn as_str(&self) -> &str {
+        self.0.as_str()
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl FromStr for Number {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::Number::from_str(s).map(Number)
+    }
+}
+
+impl From<i64> for Number {
+    fn from(value: i64) -> Self {
+        Number(value.into())
+    }
+}
+
+impl From<u64> for Number {
+    fn from(value: u64) -> Self {
+        Number(value.into())
+    }
+}
+
+impl From<serde_json::Number> for Number {
+    fn from(value: serde_json::Number) -> Self {
+        Number(value)
+    }
+}
+
+impl From<Number> for serde_json::Number {
+    fn from(value: Number) -> Self {
+        value.0
+    }
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(untagged)]
-pub enum BooleanOrIntegerOrNumber {
-    Integer(i64),
-    Number(f64),
+pub enum BooleanOrNumber {
     Boolean(bool),
+    Number(Number),
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// A schema in any position a schema may appear, where `true` and `false` are shorthands for the
+/// schema that accepts every instance and the one that accepts none.
+#[derive(Debug, Serialize, Clone)]
 #[serde(untagged)]
 pub enum BooleanOrSchema {
     Boolean(bool),
     InnerSchema(Box<Schema>),
 }
 
+impl BooleanOrSchema {
+    pub fn as_schema(&self) -> Option<&Schema> {
+        match self {
+            BooleanOrSchema::InnerSchema(schema) => Some(schema),
+            BooleanOrSchema::Boolean(_) => None,
+        }
+    }
+
+    pub fn as_schema_mut(&mut self) -> Option<&mut Schema> {
+        match self {
+            BooleanOrSchema::InnerSchema(schema) => Some(schema),
+            BooleanOrSchema::Boolean(_) => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            BooleanOrSchema::Boolean(value) => Some(*value),
+            BooleanOrSchema::InnerSchema(_) => None,
+        }
+    }
+}
+
+impl From<bool> for BooleanOrSchema {
+    fn from(value: bool) -> Self {
+        BooleanOrSchema::Boolean(value)
+    }
+}
+
+impl From<Schema> for BooleanOrSchema {
+    fn from(value: Schema) -> Self {
+        BooleanOrSchema::InnerSchema(Box::new(value))
+    }
+}
+
+// Deserialized by hand rather than as an untagged enum, which would buffer every nested schema
+// and report any error inside it as "data did not match any variant".
+impl<'de> Deserialize<'de> for BooleanOrSchema {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct BooleanOrSchemaVisitor;
+
+        impl<'de> de::Visitor<'de> for BooleanOrSchemaVisitor {
+            type Value = BooleanOrSchema;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a boolean or a schema object")
+            }
+
+            fn visit_bool<E>(self, value: bool) -> Result<Self::Value, E> {
+                Ok(BooleanOrSchema::Boolean(value))
+            }
+
+            fn visit_map<A>(self, map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                Schema::deserialize(MapAccessDeserializer::new(map)).map(BooleanOrSchema::from)
+            }
+        }
+
+        deserializer.deserialize_any(BooleanOrSchemaVisitor)
+    }
+}
+
+/// The value of `items`, which draft 2019-09 and earlier also allow to be an array of schemas
+/// matched positionally, like `prefixItems` in draft 2020-12.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum SchemaOrSchemaArray {
+    Array(Vec<BooleanOrSchema>),
+    Schema(BooleanOrSchema),
+}
+
+/// The value of a draft 7 `dependencies` entry: either the properties that become required or
+/// a schema the whole instance must then match.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum SchemaOrStringArray {
+    Array(Vec<String>),
+    Schema(BooleanOrSchema),
+}
+
+/// The JSON Schema drafts, ordered from oldest to newest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Dialect {
+    Draft4,
+    Draft6,
+    Draft7,
+    Draft2019_09,
+    Draft2020_12,
+}
+
+impl Dialect {
+    /// Detects the draft from a `$schema` URI, ignoring the scheme and an empty fragment.
+    pub fn from_uri(uri: &str) -> Option<Dialect> {
+        let uri = uri.trim_end_matches('#');
+        let uri = uri
+            .strip_prefix("https://")
+            .or_else(|| uri.strip_prefix("http://"))?;
+
+        match uri {
+            "json-schema.org/draft-04/schema" => Some(Dialect::Draft4),
+            "json-schema.org/draft-06/schema" => Some(Dialect::Draft6),
+            "json-schema.org/draft-07/schema" => Some(Dialect::Draft7),
+            "json-schema.org/draft/2019-09/schema" => Some(Dialect::Draft2019_09),
+            "json-schema.org/draft/2020-12/schema" => Some(Dialect::Draft2020_12),
+            _ => None,
+        }
+    }
+
+    /// The canonical `$schema` URI of the draft.
+    pub fn uri(&self) -> &'static str {
+        match self {
+            Dialect::Draft4 => "http://json-schema.org/draft-04/schema#",
+            Dialect::Draft6 => "http://json-schema.org/draft-06/schema#",
+            Dialect::Draft7 => "http://json-schema.org/draft-07/schema#",
+            Dialect::Draft2019_09 => "https://json-schema.org/draft/2019-09/schema",
+            Dialect::Draft2020_12 => "https://json-schema.org/draft/2020-12/schema",
+        }
+    }
+}
+
+impl Display for Dialect {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Dialect::Draft4 => write!(f, "draft-04"),
+            Dialect::Draft6 => write!(f, "draft-06"),
+            Dialect::Draft7 => write!(f, "draft-07"),
+            Dialect::Draft2019_09 => write!(f, "draft 2019-09"),
+            Dialect::Draft2020_12 => write!(f, "draft 2020-12"),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum DialectError {
+    Deserialization(serde_json::Error),
+    InvalidKeyword(Dialect, &'static str),
+}
+
+impl Error for DialectError {}
+
+impl Display for DialectError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Deserialization(error) => write!(f, "Could not deserialize the schema: {error}"),
+            Self::InvalidKeyword(dialect, keyword) => {
+                write!(
+                    f,
+                    "The schema uses a form of {keyword} that {dialect} doesn't allow."
+                )
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -466,6 +1144,67 @@ mod tests {
         assert_eq!(actual_value, expected_value);
     }
 
+    /// https://json-schema.org/understanding-json-schema/reference/const
+    #[test]
+    fn object_values_example() {
+        let json_string = r##"{
+                "const": { "country": "United States of America", "codes": [1, 2.5, null] },
+                "enum": [{ "a": 1 }, { "b": { "c": false } }],
+                "default": { "a": 1 },
+                "examples": [{}, []]
+            }"##;
+
+        let deserialized: Schema = serde_json::from_str(json_string).unwrap();
+        let schema_const = deserialized
+            .schema_const
+            .as_ref()
+            .unwrap()
+            .as_object()
+            .unwrap();
+
+        assert_eq!(
+            schema_const["country"].as_str(),
+            Some("United States of America")
+        );
+        assert!(schema_const["codes"].as_array().unwrap()[2].is_null());
+        assert!(deserialized.extra.is_empty());
+
+        let actual_value: serde_json::Value = serde_json::to_value(deserialized).unwrap();
+        let expected_value: serde_json::Value = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(actual_value, expected_value);
+    }
+
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-instance-equality
+    #[test]
+    fn any_type_equality_example() {
+        let parse = |json: &str| serde_json::from_str::<AnyType>(json).unwrap();
+
+        assert_eq!(parse("1"), parse("1.0"));
+        assert_eq!(
+            parse(r#"{ "a": 1, "b": [2.0] }"#),
+            parse(r#"{ "b": [2], "a": 1.0 }"#)
+        );
+        assert_ne!(parse("1"), parse("true"));
+        assert_ne!(parse("[1, 2]"), parse("[2, 1]"));
+        assert_ne!(parse(r#"{ "a": 1 }"#), parse(r#"{ "a": 1, "b": 2 }"#));
+        assert!(parse("2").as_number().unwrap().is_i64());
+        assert!(parse("2.0").as_number().unwrap().is_f64());
+    }
+
+    #[cfg(feature = "json_value")]
+    #[test]
+    fn json_value_conversion_example() {
+        let value = serde_json::json!({ "a": [1, 2.5, "three", null, true] });
+        let any_type = AnyType::from(value.clone());
+
+        assert_eq!(
+            any_type,
+            serde_json::from_value::<AnyType>(value.clone()).unwrap()
+        );
+        assert_eq!(serde_json::Value::from(any_type), value);
+    }
+
     /// https://json-schema.org/learn/miscellaneous-examples#regular-expression-pattern
     #[test]
     fn regex_expression_pattern_example() {
@@ -530,6 +1269,368 @@ mod tests {
         assert_eq!(actual_value, expected_value);
     }
 
+    /// https://json-schema.org/understanding-json-schema/reference/numeric#range
+    #[test]
+    fn exclusive_minimum_example() {
+        let json_string = r##"{
+                "type": "number",
+                "exclusiveMinimum": 0,
+                "maximum": 100
+            }"##;
+
+        let deserialized: Schema = serde_json::from_str(json_string).unwrap();
+
+        assert!(deserialized.extra.is_empty());
+
+        let actual_value: serde_json::Value = serde_json::to_value(deserialized).unwrap();
+        let expected_value: serde_json::Value = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(actual_value, expected_value);
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/array#items
+    #[test]
+    fn tuple_items_example() {
+        let json_string = r##"{
+                "type": "array",
+                "items": [
+                    { "type": "number" },
+                    { "type": "string" },
+                    { "enum": ["Street", "Avenue", "Boulevard"] },
+                    { "enum": ["NW", "NE", "SW", "SE"] }
+                ],
+                "additionalItems": false
+            }"##;
+
+        let deserialized: Schema = serde_json::from_str(json_string).unwrap();
+
+        assert!(deserialized.extra.is_empty());
+
+        let actual_value: serde_json::Value = serde_json::to_value(deserialized).unwrap();
+        let expected_value: serde_json::Value = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(actual_value, expected_value);
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/conditionals#dependentRequired
+    #[test]
+    fn dependencies_example() {
+        let json_string = r##"{
+                "type": "object",
+                "properties": {
+                    "name": { "type": "string" },
+                    "credit_card": { "type": "number" },
+                    "billing_address": { "type": "string" }
+                },
+                "dependencies": {
+                    "credit_card": ["billing_address"],
+                    "billing_address": {
+                        "required": ["credit_card"]
+                    }
+                }
+            }"##;
+
+        let deserialized: Schema = serde_json::from_str(json_string).unwrap();
+
+        assert!(deserialized.extra.is_empty());
+
+        let actual_value: serde_json::Value = serde_json::to_value(deserialized).unwrap();
+        let expected_value: serde_json::Value = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(actual_value, expected_value);
+    }
+
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-dynamic-references-with-dyn
+    #[test]
+    fn dynamic_reference_example() {
+        let json_string = r##"{
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$id": "https://example.com/tree",
+                "$dynamicAnchor": "node",
+                "type": "object",
+                "properties": {
+                    "data": {},
+                    "children": {
+                        "type": "array",
+                        "items": {
+                            "$dynamicRef": "#node"
+                        }
+                    }
+                }
+            }"##;
+
+        let deserialized: Schema = serde_json::from_str(json_string).unwrap();
+
+        assert!(deserialized.extra.is_empty());
+
+        let actual_value: serde_json::Value = serde_json::to_value(deserialized).unwrap();
+        let expected_value: serde_json::Value = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(actual_value, expected_value);
+    }
+
+    /// https://json-schema.org/draft/2019-09/json-schema-core#recursive-example
+    #[test]
+    fn recursive_reference_example() {
+        let json_string = r##"{
+                "$schema": "https://json-schema.org/draft/2019-09/schema",
+                "$id": "https://example.com/tree",
+                "$recursiveAnchor": true,
+                "type": "object",
+                "properties": {
+                    "data": {},
+                    "children": {
+                        "type": "array",
+                        "items": {
+                            "$recursiveRef": "#"
+                        }
+                    }
+                }
+            }"##;
+
+        let deserialized: Schema = serde_json::from_str(json_string).unwrap();
+
+        assert!(deserialized.extra.is_empty());
+
+        let actual_value: serde_json::Value = serde_json::to_value(deserialized).unwrap();
+        let expected_value: serde_json::Value = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(actual_value, expected_value);
+    }
+
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-the-vocabulary-keyword
+    #[test]
+    fn vocabulary_example() {
+        let json_string = r##"{
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$id": "https://json-schema.org/draft/2020-12/schema",
+                "$vocabulary": {
+                    "https://json-schema.org/draft/2020-12/vocab/core": true,
+                    "https://json-schema.org/draft/2020-12/vocab/applicator": true,
+                    "https://json-schema.org/draft/2020-12/vocab/unevaluated": true,
+                    "https://json-schema.org/draft/2020-12/vocab/validation": true,
+                    "https://json-schema.org/draft/2020-12/vocab/meta-data": true,
+                    "https://json-schema.org/draft/2020-12/vocab/format-annotation": true,
+                    "https://json-schema.org/draft/2020-12/vocab/content": true
+                },
+                "$dynamicAnchor": "meta",
+                "title": "Core and Validation specifications meta-schema",
+                "allOf": [
+                    { "$ref": "meta/core" },
+                    { "$ref": "meta/applicator" },
+                    { "$ref": "meta/unevaluated" },
+                    { "$ref": "meta/validation" },
+                    { "$ref": "meta/meta-data" },
+                    { "$ref": "meta/format-annotation" },
+                    { "$ref": "meta/content" }
+                ],
+                "type": ["object", "boolean"],
+                "$comment": "This meta-schema also defines keywords that have appeared in previous drafts in order to prevent incompatible extensions as they remain in common use."
+            }"##;
+
+        let deserialized: Schema = serde_json::from_str(json_string).unwrap();
+
+        assert!(deserialized.extra.is_empty());
+
+        let actual_value: serde_json::Value = serde_json::to_value(deserialized).unwrap();
+        let expected_value: serde_json::Value = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(actual_value, expected_value);
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/non_json_data#contentschema
+    #[test]
+    fn content_schema_example() {
+        let json_string = r##"{
+                "type": "string",
+                "contentMediaType": "application/json",
+                "contentSchema": {
+                    "type": "object",
+                    "required": ["name"],
+                    "properties": {
+                        "name": { "type": "string" }
+                    }
+                }
+            }"##;
+
+        let deserialized: Schema = serde_json::from_str(json_string).unwrap();
+
+        assert!(deserialized.extra.is_empty());
+
+        let actual_value: serde_json::Value = serde_json::to_value(deserialized).unwrap();
+        let expected_value: serde_json::Value = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(actual_value, expected_value);
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/object#propertyNames
+    #[test]
+    fn property_names_subschema_example() {
+        let json_string = r##"{
+                "type": "object",
+                "propertyNames": {
+                    "type": "string",
+                    "maxLength": 8,
+                    "not": { "enum": ["__proto__", "constructor"] }
+                }
+            }"##;
+
+        let deserialized: Schema = serde_json::from_str(json_string).unwrap();
+
+        assert!(deserialized.extra.is_empty());
+
+        let actual_value: serde_json::Value = serde_json::to_value(deserialized).unwrap();
+        let expected_value: serde_json::Value = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(actual_value, expected_value);
+    }
+
+    /// https://json-schema.org/draft/2020-12/json-schema-core#name-boolean-json-schemas
+    #[test]
+    fn boolean_subschemas_example() {
+        let json_string = r##"{
+                "properties": {
+                    "name": true,
+                    "password": false
+                },
+                "$defs": { "anything": true },
+                "allOf": [true, { "required": ["name"] }],
+                "not": false,
+                "if": true,
+                "then": { "minProperties": 1 },
+                "else": false
+            }"##;
+
+        let deserialized: Schema = serde_json::from_str(json_string).unwrap();
+        let properties = deserialized.properties.as_ref().unwrap();
+
+        assert_eq!(properties["name"].as_bool(), Some(true));
+        assert_eq!(properties["password"].as_bool(), Some(false));
+        assert!(deserialized.all_of.as_ref().unwrap()[1]
+            .as_schema()
+            .is_some());
+        assert!(deserialized.extra.is_empty());
+
+        let actual_value: serde_json::Value = serde_json::to_value(deserialized).unwrap();
+        let expected_value: serde_json::Value = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(actual_value, expected_value);
+
+        let root: BooleanOrSchema = serde_json::from_str("false").unwrap();
+
+        assert_eq!(root.as_bool(), Some(false));
+        assert!(serde_json::from_str::<BooleanOrSchema>("42").is_err());
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/schema#schema
+    #[test]
+    fn dialect_detection_example() {
+        let schema: Schema =
+            serde_json::from_str(r#"{ "$schema": "https://json-schema.org/draft-07/schema" }"#)
+                .unwrap();
+
+        assert_eq!(schema.dialect(), Some(Dialect::Draft7));
+        assert_eq!(
+            Dialect::from_uri("http://json-schema.org/draft-04/schema#"),
+            Some(Dialect::Draft4)
+        );
+        assert_eq!(
+            Dialect::from_uri(Dialect::Draft2019_09.uri()),
+            Some(Dialect::Draft2019_09)
+        );
+        assert_eq!(Dialect::from_uri("https://example.com/custom-meta"), None);
+    }
+
+    /// https://json-schema.org/specification-links#draft-4
+    #[test]
+    fn draft_4_dialect_example() {
+        let json_string = r##"{
+                "$schema": "http://json-schema.org/draft-04/schema#",
+                "id": "http://example.com/product.schema.json",
+                "$id": "not an identifier in draft 4",
+                "definitions": {
+                    "price": { "type": "number", "minimum": 0, "exclusiveMinimum": true }
+                },
+                "$defs": { "unused": {} },
+                "properties": {
+                    "price": { "$ref": "#/definitions/price" },
+                    "dimensions": { "items": [{ "type": "number" }, { "type": "number" }] }
+                }
+            }"##;
+
+        let schema = Schema::from_str_with_dialect(json_string, Dialect::Draft2020_12).unwrap();
+
+        assert_eq!(
+            schema.get_id().unwrap(),
+            "http://example.com/product.schema.json"
+        );
+        assert!(schema.definitions.is_none());
+        assert!(schema
+            .legacy_definitions
+            .as_ref()
+            .unwrap()
+            .contains_key("price"));
+        assert_eq!(
+            schema.get_extra("$id"),
+            Some(&serde_json::json!("not an identifier in draft 4"))
+        );
+        assert!(schema.get_extra("$defs").is_some());
+
+        let actual_value: serde_json::Value = serde_json::to_value(schema).unwrap();
+        let expected_value: serde_json::Value = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(actual_value, expected_value);
+
+        let numeric_limit = r#"{ "minimum": 0, "exclusiveMinimum": 0 }"#;
+
+        assert!(matches!(
+            Schema::from_str_with_dialect(numeric_limit, Dialect::Draft4),
+            Err(DialectError::InvalidKeyword(
+                Dialect::Draft4,
+                "exclusiveMinimum"
+            ))
+        ));
+    }
+
+    /// https://json-schema.org/draft/2020-12/release-notes
+    #[test]
+    fn draft_2020_12_dialect_example() {
+        let json_string = r##"{
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "id": "just an annotation",
+                "dependencies": { "credit_card": ["billing_address"] },
+                "additionalItems": false,
+                "properties": {
+                    "legacy": {
+                        "$schema": "http://json-schema.org/draft-07/schema#",
+                        "dependencies": { "credit_card": ["billing_address"] },
+                        "prefixItems": [{ "type": "string" }]
+                    }
+                }
+            }"##;
+
+        let schema = Schema::from_str_with_dialect(json_string, Dialect::Draft4).unwrap();
+        let legacy = schema.properties.as_ref().unwrap()["legacy"]
+            .as_schema()
+            .unwrap();
+
+        assert!(schema.get_id().is_none());
+        assert!(schema.dependencies.is_none());
+        assert!(schema.additional_items.is_none());
+        assert_eq!(schema.extra.len(), 3);
+        assert!(legacy.dependencies.is_some());
+        assert!(legacy.prefix_items.is_none());
+        assert!(legacy.get_extra("prefixItems").is_some());
+
+        let tuple =
+            r#"{ "$schema": "https://json-schema.org/draft/2020-12/schema", "items": [{}] }"#;
+
+        assert!(matches!(
+            Schema::from_str_with_dialect(tuple, Dialect::Draft2020_12),
+            Err(DialectError::InvalidKeyword(Dialect::Draft2020_12, "items"))
+        ));
+    }
+
     /// https://json-schema.org/understanding-json-schema/reference/object#size
     #[test]
     fn size_example() {
@@ -1631,4 +2732,122 @@ mod tests {
 
         assert_eq!(actual_value, expected_value);
     }
+
+    /// https://spec.openapis.org/oas/v3.1.0#discriminator-object
+    #[test]
+    fn unknown_keywords_example() {
+        let json_string = r##"{
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$vocabulary": {
+                    "https://json-schema.org/draft/2020-12/vocab/core": true
+                },
+                "oneOf": [
+                    { "$ref": "#/components/schemas/Cat" },
+                    { "$ref": "#/components/schemas/Dog" }
+                ],
+                "discriminator": {
+                    "propertyName": "petType"
+                },
+                "x-internal": true,
+                "properties": {
+                    "name": {
+                        "type": "string",
+                        "x-order": 1,
+                        "x-tags": ["a", { "nested": null }]
+                    }
+                }
+            }"##;
+
+        let mut deserialized: Schema = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(
+            deserialized.get_extra("discriminator"),
+            Some(&serde_json::json!({ "propertyName": "petType" }))
+        );
+        assert_eq!(
+            deserialized
+                .extensions()
+                .map(|(keyword, _)| keyword.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["x-internal"]
+        );
+        assert!(deserialized.get_extra("oneOf").is_none());
+
+        let actual_value: serde_json::Value = serde_json::to_value(&deserialized).unwrap();
+        let expected_value: serde_json::Value = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(actual_value, expected_value);
+
+        deserialized.insert_extra("x-added", Value::from("value"));
+
+        assert_eq!(
+            deserialized.remove_extra("x-internal"),
+            Some(Value::Bool(true))
+        );
+        assert_eq!(
+            serde_json::to_value(&deserialized).unwrap()["x-added"],
+            Value::from("value")
+        );
+    }
+
+    /// https://json-schema.org/understanding-json-schema/reference/numeric#range
+    #[test]
+    fn numeric_fidelity_example() {
+        let json_string = r#"{
+                "type": "object",
+                "properties": {
+                    "latitude": { "type": "number", "minimum": -90.5, "maximum": 90.5 },
+                    "distance": { "type": "number", "maximum": 1e300, "multipleOf": 0.01 },
+                    "count": { "type": "integer", "minimum": 18446744073709551615 }
+                },
+                "additionalProperties": { "exclusiveMaximum": 2.5 },
+                "items": { "minimum": -1.5 }
+            }"#;
+
+        let deserialized: Schema = serde_json::from_str(json_string).unwrap();
+        let properties = deserialized.properties.as_ref().unwrap();
+
+        assert_eq!(
+            properties["latitude"]
+                .as_schema()
+                .unwrap()
+                .minimum
+                .as_ref()
+                .unwrap()
+                .as_f64(),
+            Some(-90.5)
+        );
+        assert_eq!(
+            properties["count"]
+                .as_schema()
+                .unwrap()
+                .minimum
+                .as_ref()
+                .unwrap()
+                .as_u64(),
+            Some(u64::MAX)
+        );
+
+        let actual_value: serde_json::Value = serde_json::to_value(deserialized).unwrap();
+        let expected_value: serde_json::Value = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(actual_value, expected_value);
+    }
+
+    #[cfg(feature = "arbitrary_precision")]
+    #[test]
+    fn arbitrary_precision_example() {
+        let json_string = r#"{ "minimum": 0.10000000000000000000001, "maximum": 123456789012345678901234567890 }"#;
+
+        let deserialized: Schema = serde_json::from_str(json_string).unwrap();
+
+        assert_eq!(
+            deserialized.minimum.as_ref().unwrap().as_str(),
+            "0.10000000000000000000001"
+        );
+        assert_eq!(
+            serde_json::to_string(&deserialized).unwrap(),
+            r#"{"minimum":0.10000000000000000000001,"maximum":123456789012345678901234567890}"#
+        );
+    }
 }
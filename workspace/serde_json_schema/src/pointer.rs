@@ -0,0 +1,309 @@
+use std::error::Error;
+use std::fmt::Display;
+use std::str::FromStr;
+
+use crate::Schema;
+
+/// A JSON pointer as described in RFC 6901, e.g. `/$defs/order/properties/items`, kept as its
+/// unescaped reference tokens.
+#[derive(Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct JsonPointer(Vec<String>);
+
+impl JsonPointer {
+    /// The empty pointer, pointing at the whole document.
+    pub fn root() -> Self {
+        JsonPointer::default()
+    }
+
+    /// Parses a pointer in its JSON string representation, e.g. `/properties/a~1b`.
+    pub fn parse(pointer: &str) -> Result<Self, JsonPointerError> {
+        if pointer.is_empty() {
+            return Ok(JsonPointer::root());
+        }
+
+        let Some(tokens) = pointer.strip_prefix('/') else {
+            return Err(JsonPointerError::MissingLeadingSlash(pointer.to_owned()));
+        };
+
+        tokens
+            .split('/')
+            .map(|token| unescape(token).ok_or(JsonPointerError::InvalidEscape(pointer.to_owned())))
+            .collect::<Result<Vec<String>, JsonPointerError>>()
+            .map(JsonPointer)
+    }
+
+    /// Parses a pointer in its URI fragment representation, with or without the leading `#`,
+    /// e.g. `#/properties/foo%20bar`.
+    pub fn from_fragment(fragment: &str) -> Result<Self, JsonPointerError> {
+        let fragment = fragment.strip_prefix('#').unwrap_or(fragment);
+        let decoded = percent_decode(fragment).ok_or(JsonPointerError::InvalidPercentEncoding(
+            fragment.to_owned(),
+        ))?;
+
+        JsonPointer::parse(&decoded)
+    }
+
+    /// The URI fragment representation of the pointer, including the leading `#`.
+    pub fn to_fragment(&self) -> String {
+        format!("#{}", percent_encode(&self.to_string()))
+    }
+
+    pub fn is_root(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn tokens(&self) -> impl Iterator<Item = &str> {
+        self.0.iter().map(String::as_str)
+    }
+
+    pub fn push(&mut self, token: impl Into<String>) {
+        self.0.push(token.into());
+    }
+
+    pub fn pop(&mut self) -> Option<String> {
+        self.0.pop()
+    }
+
+    /// This pointer followed by a relative one.
+    pub fn join(&self, relative: &JsonPointer) -> JsonPointer {
+        JsonPointer(self.0.iter().chain(&relative.0).cloned().collect())
+    }
+}
+
+impl Display for JsonPointer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0
+            .iter()
+            .try_for_each(|token| write!(f, "/{}", token.replace('~', "~0").replace('/', "~1")))
+    }
+}
+
+impl FromStr for JsonPointer {
+    type Err = JsonPointerError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        JsonPointer::parse(s)
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for JsonPointer {
+    fn from_iter<T: IntoIterator<Item = S>>(iter: T) -> Self {
+        JsonPointer(iter.into_iter().map(Into::into).collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonPointerError {
+    MissingLeadingSlash(String),
+    InvalidEscape(String),
+    InvalidPercentEncoding(String),
+}
+
+impl Error for JsonPointerError {}
+
+impl Display for JsonPointerError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::MissingLeadingSlash(pointer) => {
+                write!(f, "The JSON pointer {pointer} doesn't start with a slash.")
+            }
+            Self::InvalidEscape(pointer) => write!(
+                f,
+                "The JSON pointer {pointer} has a ~ that isn't followed by 0 or 1."
+            ),
+            Self::InvalidPercentEncoding(fragment) => write!(
+                f,
+                "The fragment {fragment} isn't valid percent-encoded UTF-8."
+            ),
+        }
+    }
+}
+
+impl Schema {
+    /// The subschema a JSON pointer leads to, or `None` when it leads nowhere or to a boolean
+    /// schema.
+    pub fn pointer(&self, pointer: &JsonPointer) -> Option<&Schema> {
+        self.follow(&pointer.0)
+    }
+
+    pub fn pointer_mut(&mut self, pointer: &JsonPointer) -> Option<&mut Schema> {
+        self.follow_mut(&pointer.0)
+    }
+
+    fn follow(&self, tokens: &[String]) -> Option<&Schema> {
+        if tokens.is_empty() {
+            return Some(self);
+        }
+
+        self.subschemas()
+            .into_iter()
+            .find_map(|(position, subschema)| {
+                let rest = position.strip_from(tokens)?;
+                subschema.as_schema()?.follow(rest)
+            })
+    }
+
+    fn follow_mut(&mut self, tokens: &[String]) -> Option<&mut Schema> {
+        if tokens.is_empty() {
+            return Some(self);
+        }
+
+        self.subschemas_mut()
+            .into_iter()
+            .find_map(|(position, subschema)| {
+                let rest = position.strip_from(tokens)?;
+                subschema.as_schema_mut()?.follow_mut(rest)
+            })
+    }
+}
+
+fn unescape(token: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(token.len());
+    let mut chars = token.chars();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '~' => match chars.next()? {
+                '0' => unescaped.push('~'),
+                '1' => unescaped.push('/'),
+                _ => return None,
+            },
+            char => unescaped.push(char),
+        }
+    }
+
+    Some(unescaped)
+}
+
+/// Decodes the percent-encoded bytes of a URI component, which fails on a malformed escape or
+/// when the bytes aren't UTF-8.
+pub fn percent_decode(component: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(component.len());
+    let mut rest = component.as_bytes();
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        match byte {
+            b'%' => {
+                let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+                rest = &tail[2..];
+            }
+            byte => {
+                bytes.push(byte);
+                rest = tail;
+            }
+        }
+    }
+
+    String::from_utf8(bytes).ok()
+}
+
+/// Percent-encodes every byte that may not appear in a URI fragment as is.
+fn percent_encode(pointer: &str) -> String {
+    pointer
+        .bytes()
+        .map(|byte| match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' => (byte as char).to_string(),
+            b'-' | b'.' | b'_' | b'~' | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+'
+            | b',' | b';' | b'=' | b':' | b'@' | b'/' | b'?' => (byte as char).to_string(),
+            byte => format!("%{byte:02X}"),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// https://www.rfc-editor.org/rfc/rfc6901#section-5
+    #[test]
+    fn json_pointer_example() {
+        let examples = [
+            ("", vec![]),
+            ("/foo", vec!["foo"]),
+            ("/foo/0", vec!["foo", "0"]),
+            ("/", vec![""]),
+            ("/a~1b", vec!["a/b"]),
+            ("/m~0n", vec!["m~n"]),
+            ("/ ", vec![" "]),
+        ];
+
+        for (string, tokens) in examples {
+            let pointer = JsonPointer::parse(string).unwrap();
+
+            assert_eq!(pointer.tokens().collect::<Vec<&str>>(), tokens);
+            assert_eq!(pointer.to_string(), string);
+        }
+
+        assert_eq!(
+            JsonPointer::parse("foo"),
+            Err(JsonPointerError::MissingLeadingSlash("foo".to_owned()))
+        );
+        assert_eq!(
+            JsonPointer::parse("/a~2b"),
+            Err(JsonPointerError::InvalidEscape("/a~2b".to_owned()))
+        );
+    }
+
+    /// https://www.rfc-editor.org/rfc/rfc6901#section-6
+    #[test]
+    fn uri_fragment_example() {
+        let examples = [
+            ("#", vec![]),
+            ("#/c%25d", vec!["c%d"]),
+            ("#/e%5Ef", vec!["e^f"]),
+            ("#/g%7Ch", vec!["g|h"]),
+            ("#/k%22l", vec!["k\"l"]),
+            ("#/%20", vec![" "]),
+            ("#/m~0n", vec!["m~n"]),
+            ("#/a~1b", vec!["a/b"]),
+        ];
+
+        for (fragment, tokens) in examples {
+            let pointer = JsonPointer::from_fragment(fragment).unwrap();
+
+            assert_eq!(pointer.tokens().collect::<Vec<&str>>(), tokens);
+            assert_eq!(pointer.to_fragment(), fragment);
+        }
+
+        assert!(JsonPointer::from_fragment("#/%FF").is_err());
+        assert!(JsonPointer::from_fragment("#/%2").is_err());
+    }
+
+    #[test]
+    fn schema_pointer_example() {
+        let mut schema: Schema = serde_json::from_value(json!({
+            "$defs": {
+                "order": {
+                    "properties": {
+                        "items": { "type": "array", "items": [{ "type": "string" }, false] },
+                        "a/b": { "title": "Escaped" }
+                    }
+                }
+            }
+        }))
+        .unwrap();
+
+        let items: JsonPointer = "/$defs/order/properties/items".parse().unwrap();
+        let first = items.join(&JsonPointer::from_iter(["items", "0"]));
+
+        assert!(schema.pointer(&items).unwrap().items.is_some());
+        assert!(schema.pointer(&first).unwrap().schema_type.is_some());
+        assert!(schema
+            .pointer(&JsonPointer::from_fragment("#/$defs/order/properties/a~1b").unwrap())
+            .is_some());
+        assert!(schema
+            .pointer(&items.join(&JsonPointer::from_iter(["items", "1"])))
+            .is_none());
+        assert!(schema
+            .pointer(&"/$defs/customer".parse().unwrap())
+            .is_none());
+        assert!(schema.pointer(&"/$defs".parse().unwrap()).is_none());
+
+        schema.pointer_mut(&first).unwrap().min_length = Some(1);
+
+        assert_eq!(schema.pointer(&first).unwrap().min_length, Some(1));
+    }
+}
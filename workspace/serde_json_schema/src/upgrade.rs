@@ -0,0 +1,623 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use serde_json::Value;
+
+use crate::{walk_schema_mut, BooleanOrNumber, Dialect, Number, Schema, SchemaOrSchemaArray};
+use crate::{JsonPointer, SchemaOrStringArray, VisitorMut};
+use crate::{DEFINITIONS_PATH, LEGACY_DEFINITIONS_PATH};
+
+/// Keywords whose next JSON pointer token is a name or an index rather than a keyword.
+const KEYED_KEYWORDS: [&str; 10] = [
+    "properties",
+    "patternProperties",
+    "$defs",
+    "definitions",
+    "dependentSchemas",
+    "dependencies",
+    "allOf",
+    "anyOf",
+    "oneOf",
+    "prefixItems",
+];
+
+/// Keywords next to a `$ref` that don't change what an instance must look like, so it makes no
+/// difference that drafts before 2019-09 ignore them.
+const INERT_KEYWORDS: [&str; 16] = [
+    "$ref",
+    "$schema",
+    "$id",
+    "id",
+    "$anchor",
+    "$vocabulary",
+    "$comment",
+    "title",
+    "description",
+    "default",
+    "examples",
+    "readOnly",
+    "writeOnly",
+    "deprecated",
+    "$defs",
+    "definitions",
+];
+
+/// A part of a schema that [`Schema::upgrade`] left untouched because it has no 2020-12
+/// equivalent or because migrating it would overwrite something.
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpgradeIssue {
+    location: JsonPointer,
+    kind: UpgradeIssueKind,
+}
+
+impl UpgradeIssue {
+    /// The JSON pointer to the schema in the original document.
+    pub fn location(&self) -> &JsonPointer {
+        &self.location
+    }
+
+    pub fn kind(&self) -> &UpgradeIssueKind {
+        &self.kind
+    }
+}
+
+impl Display for UpgradeIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at \"{}\"", self.kind, self.location)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum UpgradeIssueKind {
+    ConflictingIdentifier(String),
+    ConflictingAnchor(String),
+    ConflictingDefinition(String),
+    ConflictingDependency(String),
+    ConflictingPrefixItems,
+    ExclusiveLimitWithoutLimit(&'static str),
+    RecursiveReference,
+    /// Keywords next to a `$ref`, which drafts before 2019-09 ignore and 2020-12 applies.
+    ReferenceSiblings(Vec<String>),
+}
+
+impl Display for UpgradeIssueKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ConflictingIdentifier(id) => {
+                write!(
+                    f,
+                    "id {id} was kept, as the schema already has a different $id"
+                )
+            }
+            Self::ConflictingAnchor(id) => {
+                write!(
+                    f,
+                    "$id {id} was kept, as the schema already has a different $anchor"
+                )
+            }
+            Self::ConflictingDefinition(name) => {
+                write!(
+                    f,
+                    "definitions/{name} was kept, as $defs already has {name}"
+                )
+            }
+            Self::ConflictingDependency(name) => write!(
+                f,
+                "dependencies/{name} was kept, as the schema already has a dependency on {name}"
+            ),
+            Self::ConflictingPrefixItems => write!(
+                f,
+                "The items array was kept, as the schema already has prefixItems"
+            ),
+            Self::ExclusiveLimitWithoutLimit(keyword) => write!(
+                f,
+                "{keyword} was kept, as there is no limit to make exclusive"
+            ),
+            Self::RecursiveReference => write!(
+                f,
+                "$recursiveRef and $recursiveAnchor have no exact 2020-12 equivalent"
+            ),
+            Self::ReferenceSiblings(keywords) => write!(
+                f,
+                "{} next to $ref used to be ignored, but now apply",
+                keywords.join(", ")
+            ),
+        }
+    }
+}
+
+impl Schema {
+    /// Rewrites a draft-04 to 2019-09 schema into its draft 2020-12 form: `id` becomes `$id`,
+    /// plain name fragments in `id` or `$id` become `$anchor`, `definitions` becomes `$defs`,
+    /// array `items` become `prefixItems`, boolean exclusive limits become numeric and
+    /// `dependencies` is split into `dependentRequired` and `dependentSchemas`. `$ref` pointers into renamed keywords are rewritten too, assuming the
+    /// schemas they point at get upgraded as well.
+    ///
+    /// Whatever can't be migrated is left as it was and reported, as are the keywords next to a
+    /// `$ref` that start to apply, unless the schema declares a draft that already applied them.
+    pub fn upgrade(&mut self) -> Vec<UpgradeIssue> {
+        let mut upgrader = Upgrader {
+            issues: Vec::new(),
+            dialects: Vec::new(),
+        };
+        upgrader.visit_schema_mut(&JsonPointer::root(), self);
+        self.schema = Some(Dialect::Draft2020_12.uri().to_owned());
+
+        upgrader.issues
+    }
+}
+
+struct Upgrader {
+    issues: Vec<UpgradeIssue>,
+    /// The draft declared by each schema on the way to the current one, or inherited from the
+    /// nearest one declaring it.
+    dialects: Vec<Option<Dialect>>,
+}
+
+impl VisitorMut for Upgrader {
+    // Subschemas are upgraded before their parent, while their pointers still match the original
+    // document.
+    fn visit_schema_mut(&mut self, pointer: &JsonPointer, schema: &mut Schema) {
+        let dialect = schema.dialect().or(self.dialects.last().copied().flatten());
+
+        self.dialects.push(dialect);
+        walk_schema_mut(self, pointer, schema);
+        self.dialects.pop();
+
+        upgrade(schema, pointer, dialect, &mut self.issues);
+    }
+}
+
+fn upgrade(
+    schema: &mut Schema,
+    location: &JsonPointer,
+    dialect: Option<Dialect>,
+    issues: &mut Vec<UpgradeIssue>,
+) {
+    let mut report = |kind| {
+        issues.push(UpgradeIssue {
+            location: location.clone(),
+            kind,
+        })
+    };
+
+    if schema.reference.is_some() && dialect.is_none_or(|dialect| dialect <= Dialect::Draft7) {
+        let siblings = reference_siblings(schema);
+
+        if !siblings.is_empty() {
+            report(UpgradeIssueKind::ReferenceSiblings(siblings));
+        }
+    }
+
+    if schema
+        .dialect()
+        .is_some_and(|dialect| dialect < Dialect::Draft2020_12)
+    {
+        schema.schema = Some(Dialect::Draft2020_12.uri().to_owned());
+    }
+
+    if let Some(id) = schema.id.take() {
+        match (id.strip_prefix('#'), &schema.dollar_id) {
+            // Draft 4 declares plain name fragments with `id`, which later drafts call anchors.
+            (Some(anchor), _) if schema.anchor.is_none() => schema.anchor = Some(anchor.to_owned()),
+            (None, None) => schema.dollar_id = Some(id),
+            (None, Some(dollar_id)) if *dollar_id == id => {}
+            _ => {
+                report(UpgradeIssueKind::ConflictingIdentifier(id.clone()));
+                schema.id = Some(id);
+            }
+        }
+    }
+
+    // Drafts 6 and 7 declare them with `$id`, which may no longer be a fragment.
+    if let Some(id) = schema.dollar_id.take_if(|id| id.starts_with('#')) {
+        match (&id[1..], &schema.anchor) {
+            ("", _) => {}
+            (anchor, None) => schema.anchor = Some(anchor.to_owned()),
+            (anchor, Some(existing)) if anchor == existing => {}
+            _ => {
+                report(UpgradeIssueKind::ConflictingAnchor(id.clone()));
+                schema.dollar_id = Some(id);
+            }
+        }
+    }
+
+    if let Some(legacy_definitions) = schema.legacy_definitions.take() {
+        let definitions = schema.definitions.get_or_insert_with(HashMap::new);
+        let conflicts = move_entries(legacy_definitions, definitions);
+
+        for name in conflicts.keys() {
+            report(UpgradeIssueKind::ConflictingDefinition(name.to_owned()));
+        }
+
+        schema.legacy_definitions = Some(conflicts).filter(|conflicts| !conflicts.is_empty());
+    }
+
+    match schema.items.take() {
+        Some(SchemaOrSchemaArray::Array(items)) if schema.prefix_items.is_none() => {
+            schema.prefix_items = Some(items);
+            schema.items = schema
+                .additional_items
+                .take()
+                .map(SchemaOrSchemaArray::Schema);
+        }
+        Some(SchemaOrSchemaArray::Array(items)) => {
+            report(UpgradeIssueKind::ConflictingPrefixItems);
+            schema.items = Some(SchemaOrSchemaArray::Array(items));
+        }
+        items => {
+            // Without an items array, additionalItems has no effect in any draft.
+            schema.items = items;
+            schema.additional_items = None;
+        }
+    }
+
+    let limits = [
+        (
+            "exclusiveMinimum",
+            &mut schema.minimum,
+            &mut schema.exclusive_minimum,
+        ),
+        (
+            "exclusiveMaximum",
+            &mut schema.maximum,
+            &mut schema.exlusive_maximum,
+        ),
+    ];
+
+    for (keyword, limit, exclusive) in limits {
+        if !upgrade_exclusive_limit(limit, exclusive) {
+            report(UpgradeIssueKind::ExclusiveLimitWithoutLimit(keyword));
+        }
+    }
+
+    if let Some(dependencies) = schema.dependencies.take() {
+        let mut conflicts = HashMap::new();
+
+        for (name, dependency) in dependencies {
+            let conflict = match dependency {
+                SchemaOrStringArray::Array(required) => {
+                    let dependent_required =
+                        schema.dependent_required.get_or_insert_with(HashMap::new);
+                    match dependent_required.contains_key(&name) {
+                        true => Some(SchemaOrStringArray::Array(required)),
+                        false => {
+                            dependent_required.insert(name.clone(), required);
+                            None
+                        }
+                    }
+                }
+                SchemaOrStringArray::Schema(dependent) => {
+                    let dependent_schemas =
+                        schema.dependent_schemas.get_or_insert_with(HashMap::new);
+                    match dependent_schemas.contains_key(&name) {
+                        true => Some(SchemaOrStringArray::Schema(dependent)),
+                        false => {
+                            dependent_schemas.insert(name.clone(), dependent);
+                            None
+                        }
+                    }
+                }
+            };
+
+            if let Some(conflict) = conflict {
+                report(UpgradeIssueKind::ConflictingDependency(name.clone()));
+                conflicts.insert(name, conflict);
+            }
+        }
+
+        schema.dependencies = Some(conflicts).filter(|conflicts| !conflicts.is_empty());
+        schema.dependent_required = schema
+            .dependent_required
+            .take()
+            .filter(|map| !map.is_empty());
+        schema.dependent_schemas = schema
+            .dependent_schemas
+            .take()
+            .filter(|map| !map.is_empty());
+    }
+
+    if schema.recursive_reference.is_some() || schema.recursive_anchor.is_some() {
+        report(UpgradeIssueKind::RecursiveReference);
+    }
+
+    if let Some(reference) = &schema.reference {
+        schema.reference = Some(upgrade_reference(reference));
+    }
+}
+
+/// The keywords next to the `$ref` of a schema that affect validation, in alphabetical order.
+fn reference_siblings(schema: &Schema) -> Vec<String> {
+    let Ok(Value::Object(keywords)) = serde_json::to_value(schema) else {
+        return Vec::new();
+    };
+
+    let mut siblings = keywords
+        .into_iter()
+        .map(|(keyword, _)| keyword)
+        .filter(|keyword| {
+            !INERT_KEYWORDS.contains(&keyword.as_str()) && !schema.extra.contains_key(keyword)
+        })
+        .collect::<Vec<String>>();
+    siblings.sort();
+
+    siblings
+}
+
+/// Moves every entry that doesn't overwrite an existing one, returning those that would.
+fn move_entries<V>(from: HashMap<String, V>, to: &mut HashMap<String, V>) -> HashMap<String, V> {
+    from.into_iter()
+        .filter_map(|(key, value)| match to.contains_key(&key) {
+            true => Some((key, value)),
+            false => {
+                to.insert(key, value);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Turns a draft 4 boolean exclusive limit into the numeric form, returning false when there is
+/// no limit to take the value from.
+fn upgrade_exclusive_limit(
+    limit: &mut Option<Number>,
+    exclusive: &mut Option<BooleanOrNumber>,
+) -> bool {
+    match exclusive {
+        Some(BooleanOrNumber::Boolean(true)) => match limit.take() {
+            Some(limit) => {
+                *exclusive = Some(BooleanOrNumber::Number(limit));
+                true
+            }
+            None => false,
+        },
+        Some(BooleanOrNumber::Boolean(false)) => {
+            *exclusive = None;
+            true
+        }
+        _ => true,
+    }
+}
+
+/// Rewrites the JSON pointer fragment of a reference to follow the renamed keywords, so
+/// `#/definitions/a/items/0` becomes `#/$defs/a/prefixItems/0`.
+fn upgrade_reference(reference: &str) -> String {
+    let Some((document, pointer)) = reference.split_once("#/") else {
+        return reference.to_owned();
+    };
+
+    let tokens: Vec<&str> = pointer.split('/').collect();
+    let mut upgraded = Vec::with_capacity(tokens.len());
+    let mut index = 0;
+
+    while index < tokens.len() {
+        let token = tokens[index];
+        let next = tokens.get(index + 1);
+        let is_tuple = token == "items" && next.is_some_and(|next| next.parse::<usize>().is_ok());
+
+        upgraded.push(match token {
+            LEGACY_DEFINITIONS_PATH => DEFINITIONS_PATH,
+            "items" if is_tuple => "prefixItems",
+            "additionalItems" => "items",
+            "dependencies" => "dependentSchemas",
+            _ => token,
+        });
+
+        if let Some(next) = next.filter(|_| is_tuple || KEYED_KEYWORDS.contains(&token)) {
+            upgraded.push(next);
+            index += 1;
+        }
+
+        index += 1;
+    }
+
+    format!("{document}#/{}", upgraded.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    /// https://json-schema.org/draft/2020-12/release-notes
+    #[test]
+    fn draft_4_upgrade_example() {
+        let mut schema: Schema = serde_json::from_value(json!({
+            "$schema": "http://json-schema.org/draft-04/schema#",
+            "id": "https://example.com/order.schema.json",
+            "properties": {
+                "total": { "type": "number", "minimum": 0, "exclusiveMinimum": true },
+                "discount": { "maximum": 100, "exclusiveMaximum": false },
+                "line": { "$ref": "#/definitions/line" },
+                "position": { "$ref": "#/definitions/line/items/1" }
+            },
+            "dependencies": {
+                "coupon": ["discount"],
+                "discount": { "required": ["coupon"] }
+            },
+            "definitions": {
+                "line": {
+                    "id": "#line",
+                    "type": "array",
+                    "items": [{ "type": "string" }, { "type": "integer" }],
+                    "additionalItems": false
+                }
+            }
+        }))
+        .unwrap();
+
+        let issues = schema.upgrade();
+
+        assert_eq!(issues, Vec::new());
+        assert_eq!(
+            serde_json::to_value(schema).unwrap(),
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "$id": "https://example.com/order.schema.json",
+                "properties": {
+                    "total": { "type": "number", "exclusiveMinimum": 0 },
+                    "discount": { "maximum": 100 },
+                    "line": { "$ref": "#/$defs/line" },
+                    "position": { "$ref": "#/$defs/line/prefixItems/1" }
+                },
+                "dependentRequired": { "coupon": ["discount"] },
+                "dependentSchemas": { "discount": { "required": ["coupon"] } },
+                "$defs": {
+                    "line": {
+                        "$anchor": "line",
+                        "type": "array",
+                        "prefixItems": [{ "type": "string" }, { "type": "integer" }],
+                        "items": false
+                    }
+                }
+            })
+        );
+    }
+
+    /// https://json-schema.org/draft/2019-09/release-notes#core-vocabulary
+    #[test]
+    fn draft_7_anchor_upgrade_example() {
+        let mut schema: Schema = serde_json::from_value(json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "properties": { "lines": { "items": { "$ref": "#line" } } },
+            "definitions": {
+                "line": { "$id": "#line", "type": "object" },
+                "item": { "$id": "#item", "$anchor": "product" }
+            }
+        }))
+        .unwrap();
+
+        let issues = schema.upgrade();
+
+        assert_eq!(
+            issues,
+            [UpgradeIssue {
+                location: "/definitions/item".parse().unwrap(),
+                kind: UpgradeIssueKind::ConflictingAnchor("#item".to_owned()),
+            }]
+        );
+        assert_eq!(
+            serde_json::to_value(schema).unwrap(),
+            json!({
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "properties": { "lines": { "items": { "$ref": "#line" } } },
+                "$defs": {
+                    "line": { "$anchor": "line", "type": "object" },
+                    "item": { "$id": "#item", "$anchor": "product" }
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn upgrade_issues_example() {
+        let mut schema: Schema = serde_json::from_value(json!({
+            "$id": "https://example.com/tree.schema.json",
+            "id": "https://example.com/legacy-tree.schema.json",
+            "$defs": { "node": {} },
+            "definitions": { "node": { "type": "object" } },
+            "properties": {
+                "depth": { "exclusiveMaximum": true },
+                "children": { "items": { "$recursiveRef": "#" } }
+            }
+        }))
+        .unwrap();
+
+        let issues = schema.upgrade();
+        let mut locations: Vec<(String, &UpgradeIssueKind)> = issues
+            .iter()
+            .map(|issue| (issue.location().to_string(), issue.kind()))
+            .collect();
+        locations.sort_by(|(left, _), (right, _)| left.cmp(right));
+
+        assert_eq!(
+            locations,
+            vec![
+                (
+                    "".to_owned(),
+                    &UpgradeIssueKind::ConflictingIdentifier(
+                        "https://example.com/legacy-tree.schema.json".to_owned()
+                    )
+                ),
+                (
+                    "".to_owned(),
+                    &UpgradeIssueKind::ConflictingDefinition("node".to_owned())
+                ),
+                (
+                    "/properties/children/items".to_owned(),
+                    &UpgradeIssueKind::RecursiveReference
+                ),
+                (
+                    "/properties/depth".to_owned(),
+                    &UpgradeIssueKind::ExclusiveLimitWithoutLimit("exclusiveMaximum")
+                ),
+            ]
+        );
+        assert!(schema.legacy_definitions.is_some());
+        assert_eq!(
+            schema.id.as_deref(),
+            Some("https://example.com/legacy-tree.schema.json")
+        );
+    }
+
+    /// https://json-schema.org/draft/2019-09/release-notes#core-vocabulary
+    #[test]
+    fn reference_siblings_example() {
+        let mut schema: Schema = serde_json::from_value(json!({
+            "$schema": "http://json-schema.org/draft-07/schema#",
+            "properties": {
+                "name": {
+                    "$ref": "#/definitions/name",
+                    "title": "Name",
+                    "maxLength": 20,
+                    "type": "string",
+                    "x-order": 1
+                },
+                "nickname": { "$ref": "#/definitions/name", "description": "Optional" }
+            },
+            "definitions": { "name": { "type": "string" } }
+        }))
+        .unwrap();
+
+        let issues = schema.upgrade();
+
+        assert_eq!(
+            issues,
+            vec![UpgradeIssue {
+                location: "/properties/name".parse().unwrap(),
+                kind: UpgradeIssueKind::ReferenceSiblings(vec![
+                    "maxLength".to_owned(),
+                    "type".to_owned()
+                ]),
+            }]
+        );
+
+        let mut schema: Schema = serde_json::from_value(json!({
+            "$schema": "https://json-schema.org/draft/2019-09/schema",
+            "properties": { "name": { "$ref": "#/$defs/name", "maxLength": 20 } },
+            "$defs": { "name": { "type": "string" } }
+        }))
+        .unwrap();
+
+        assert_eq!(schema.upgrade(), Vec::new());
+    }
+
+    #[test]
+    fn upgrade_reference_example() {
+        assert_eq!(
+            upgrade_reference("other.json#/definitions/a/properties/definitions/items/0"),
+            "other.json#/$defs/a/properties/definitions/prefixItems/0"
+        );
+        assert_eq!(
+            upgrade_reference("#/definitions/tuple/additionalItems"),
+            "#/$defs/tuple/items"
+        );
+        assert_eq!(
+            upgrade_reference("#/dependencies/a/items"),
+            "#/dependentSchemas/a/items"
+        );
+        assert_eq!(upgrade_reference("#anchor"), "#anchor");
+        assert_eq!(upgrade_reference("#"), "#");
+    }
+}
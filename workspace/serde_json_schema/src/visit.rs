@@ -0,0 +1,268 @@
+use crate::{BooleanOrSchema, JsonPointer, Schema};
+
+/// Where a subschema sits in the schema containing it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Position<'a> {
+    /// A keyword taking a single subschema, e.g. `not` or `additionalProperties`.
+    Keyword(&'static str),
+    /// An entry of a keyword taking an object of subschemas, e.g. `properties/name`.
+    Entry(&'static str, &'a str),
+    /// An item of a keyword taking an array of subschemas, e.g. `allOf/0`.
+    Index(&'static str, usize),
+}
+
+impl<'a> Position<'a> {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            Position::Keyword(keyword)
+            | Position::Entry(keyword, _)
+            | Position::Index(keyword, _) => keyword,
+        }
+    }
+
+    /// The JSON pointer from the containing schema to the subschema, e.g. `/properties/name`.
+    pub fn to_pointer(&self) -> JsonPointer {
+        match self {
+            Position::Keyword(keyword) => JsonPointer::from_iter([*keyword]),
+            Position::Entry(keyword, key) => JsonPointer::from_iter([*keyword, key]),
+            Position::Index(keyword, index) => {
+                JsonPointer::from_iter([keyword.to_string(), index.to_string()])
+            }
+        }
+    }
+
+    /// The tokens left after the ones leading to this position, if they do.
+    pub(crate) fn strip_from<'t>(&self, tokens: &'t [String]) -> Option<&'t [String]> {
+        match (self, tokens) {
+            (Position::Keyword(keyword), [first, rest @ ..]) if first == keyword => Some(rest),
+            (Position::Entry(keyword, key), [first, second, rest @ ..])
+                if first == keyword && second == key =>
+            {
+                Some(rest)
+            }
+            (Position::Index(keyword, index), [first, second, rest @ ..])
+                if first == keyword && *second == index.to_string() =>
+            {
+                Some(rest)
+            }
+            _ => None,
+        }
+    }
+}
+
+/// Walks a schema and every subschema in it, depth first, with the JSON pointer of each from the
+/// schema the walk started at.
+///
+/// Every method walks on by default, so an implementation only overrides the ones it cares about
+/// and calls [`walk_schema`] wherever it wants to keep descending.
+pub trait Visitor<'a> {
+    fn visit_schema(&mut self, pointer: &JsonPointer, schema: &'a Schema) {
+        walk_schema(self, pointer, schema);
+    }
+
+    fn visit_subschema(
+        &mut self,
+        pointer: &JsonPointer,
+        _position: Position<'a>,
+        subschema: &'a BooleanOrSchema,
+    ) {
+        if let BooleanOrSchema::InnerSchema(schema) = subschema {
+            self.visit_schema(pointer, schema);
+        }
+    }
+}
+
+/// Visits the subschemas directly nested in a schema.
+pub fn walk_schema<'a, V>(visitor: &mut V, pointer: &JsonPointer, schema: &'a Schema)
+where
+    V: Visitor<'a> + ?Sized,
+{
+    for (position, subschema) in schema.subschemas() {
+        let pointer = pointer.join(&position.to_pointer());
+        visitor.visit_subschema(&pointer, position, subschema);
+    }
+}
+
+/// Like [`Visitor`], for changing schemas in place.
+pub trait VisitorMut {
+    fn visit_schema_mut(&mut self, pointer: &JsonPointer, schema: &mut Schema) {
+        walk_schema_mut(self, pointer, schema);
+    }
+
+    fn visit_subschema_mut(
+        &mut self,
+        pointer: &JsonPointer,
+        _position: Position<'_>,
+        subschema: &mut BooleanOrSchema,
+    ) {
+        if let BooleanOrSchema::InnerSchema(schema) = subschema {
+            self.visit_schema_mut(pointer, schema);
+        }
+    }
+}
+
+/// Visits the subschemas directly nested in a schema, mutably.
+pub fn walk_schema_mut<V>(visitor: &mut V, pointer: &JsonPointer, schema: &mut Schema)
+where
+    V: VisitorMut + ?Sized,
+{
+    for (position, subschema) in schema.subschemas_mut() {
+        let pointer = pointer.join(&position.to_pointer());
+        visitor.visit_subschema_mut(&pointer, position, subschema);
+    }
+}
+
+/// Rebuilds a schema from owned parts, for rewrites that replace subschemas wholesale, such as
+/// turning a boolean subschema into an object one.
+pub trait Fold {
+    fn fold_schema(&mut self, pointer: &JsonPointer, schema: Schema) -> Schema {
+        fold_subschemas(self, pointer, schema)
+    }
+
+    fn fold_subschema(
+        &mut self,
+        pointer: &JsonPointer,
+        _position: Position<'_>,
+        subschema: BooleanOrSchema,
+    ) -> BooleanOrSchema {
+        match subschema {
+            BooleanOrSchema::InnerSchema(schema) => self.fold_schema(pointer, *schema).into(),
+            BooleanOrSchema::Boolean(value) => BooleanOrSchema::Boolean(value),
+        }
+    }
+}
+
+/// Folds the subschemas directly nested in a schema.
+pub fn fold_subschemas<F>(folder: &mut F, pointer: &JsonPointer, mut schema: Schema) -> Schema
+where
+    F: Fold + ?Sized,
+{
+    for (position, subschema) in schema.subschemas_mut() {
+        let pointer = pointer.join(&position.to_pointer());
+        let owned = std::mem::replace(subschema, BooleanOrSchema::Boolean(true));
+        *subschema = folder.fold_subschema(&pointer, position, owned);
+    }
+
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn example() -> Schema {
+        serde_json::from_value(json!({
+            "properties": {
+                "a/b": { "not": true },
+                "c": { "items": [{}, false] }
+            },
+            "allOf": [{ "additionalProperties": true }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn visitor_example() {
+        struct Collector(Vec<(String, &'static str, Option<bool>)>);
+
+        impl<'a> Visitor<'a> for Collector {
+            fn visit_subschema(
+                &mut self,
+                pointer: &JsonPointer,
+                position: Position<'a>,
+                subschema: &'a BooleanOrSchema,
+            ) {
+                self.0
+                    .push((pointer.to_string(), position.keyword(), subschema.as_bool()));
+                if let Some(schema) = subschema.as_schema() {
+                    walk_schema(self, pointer, schema);
+                }
+            }
+        }
+
+        let schema = example();
+        let mut collector = Collector(Vec::new());
+        collector.visit_schema(&JsonPointer::root(), &schema);
+
+        let visited = |pointer: &str, keyword, value| (pointer.to_owned(), keyword, value);
+
+        assert_eq!(
+            collector.0,
+            vec![
+                visited("/properties/a~1b", "properties", None),
+                visited("/properties/a~1b/not", "not", Some(true)),
+                visited("/properties/c", "properties", None),
+                visited("/properties/c/items/0", "items", None),
+                visited("/properties/c/items/1", "items", Some(false)),
+                visited("/allOf/0", "allOf", None),
+                visited(
+                    "/allOf/0/additionalProperties",
+                    "additionalProperties",
+                    Some(true)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn visitor_mut_example() {
+        struct Describe;
+
+        impl VisitorMut for Describe {
+            fn visit_schema_mut(&mut self, pointer: &JsonPointer, schema: &mut Schema) {
+                schema.description = Some(format!("Found at \"{pointer}\""));
+                walk_schema_mut(self, pointer, schema);
+            }
+        }
+
+        let mut schema = example();
+        Describe.visit_schema_mut(&JsonPointer::root(), &mut schema);
+
+        let c = schema.properties.as_ref().unwrap()["c"]
+            .as_schema()
+            .unwrap();
+
+        assert_eq!(schema.description.as_deref(), Some("Found at \"\""));
+        assert_eq!(c.description.as_deref(), Some("Found at \"/properties/c\""));
+    }
+
+    #[test]
+    fn fold_example() {
+        // Spells boolean subschemas out, e.g. for tools that only understand object schemas.
+        struct ExpandBooleans;
+
+        impl Fold for ExpandBooleans {
+            fn fold_subschema(
+                &mut self,
+                pointer: &JsonPointer,
+                _position: Position<'_>,
+                subschema: BooleanOrSchema,
+            ) -> BooleanOrSchema {
+                let schema = match subschema {
+                    BooleanOrSchema::Boolean(true) => serde_json::from_value(json!({})).unwrap(),
+                    BooleanOrSchema::Boolean(false) => {
+                        serde_json::from_value(json!({ "not": {} })).unwrap()
+                    }
+                    BooleanOrSchema::InnerSchema(schema) => *schema,
+                };
+
+                self.fold_schema(pointer, schema).into()
+            }
+        }
+
+        let schema = ExpandBooleans.fold_schema(&JsonPointer::root(), example());
+
+        assert_eq!(
+            serde_json::to_value(schema).unwrap(),
+            json!({
+                "properties": {
+                    "a/b": { "not": {} },
+                    "c": { "items": [{}, { "not": {} }] }
+                },
+                "allOf": [{ "additionalProperties": {} }]
+            })
+        );
+    }
+}